@@ -0,0 +1,160 @@
+use eframe::egui::{Color32, FontId, TextFormat, text::LayoutJob};
+
+/// Terminal palette for SGR 30–37 / 90–97 (normal, then bright).
+const PALETTE: [Color32; 16] = [
+    Color32::from_rgb(0, 0, 0),
+    Color32::from_rgb(205, 49, 49),
+    Color32::from_rgb(13, 188, 121),
+    Color32::from_rgb(229, 229, 16),
+    Color32::from_rgb(36, 114, 200),
+    Color32::from_rgb(188, 63, 188),
+    Color32::from_rgb(17, 168, 205),
+    Color32::from_rgb(229, 229, 229),
+    Color32::from_rgb(102, 102, 102),
+    Color32::from_rgb(241, 76, 76),
+    Color32::from_rgb(35, 209, 139),
+    Color32::from_rgb(245, 245, 67),
+    Color32::from_rgb(59, 142, 234),
+    Color32::from_rgb(214, 112, 214),
+    Color32::from_rgb(41, 184, 219),
+    Color32::from_rgb(255, 255, 255),
+];
+
+#[derive(Clone, Copy, Default)]
+struct Style {
+    fg: Option<Color32>,
+    bold: bool,
+}
+
+/// Lay out one console line, interpreting SGR escapes (colors, bold, reset)
+/// and dropping any other escape sequence.
+///
+/// - `default`: text color when no SGR color is active
+/// - `strong`: used for bold text without an explicit color (egui has no
+///   bold weight for the monospace font)
+pub fn layout_line(line: &str, font: FontId, default: Color32, strong: Color32) -> LayoutJob {
+    let mut job = LayoutJob::default();
+    let mut style = Style::default();
+
+    for (seq, text) in split_escapes(line) {
+        if let Some(params) = seq {
+            apply_sgr(&mut style, params);
+        }
+        if !text.is_empty() {
+            let color = match (style.fg, style.bold) {
+                (Some(c), _) => c,
+                (None, true) => strong,
+                (None, false) => default,
+            };
+            job.append(text, 0.0, TextFormat::simple(font.clone(), color));
+        }
+    }
+    if job.sections.is_empty() {
+        // keep blank lines one row tall
+        job.append("", 0.0, TextFormat::simple(font, default));
+    }
+    job
+}
+
+/// The line with all escape sequences removed (for filtering/copying).
+pub fn strip(line: &str) -> String {
+    if !line.contains('\x1b') {
+        return line.to_string();
+    }
+    split_escapes(line).map(|(_, text)| text).collect()
+}
+
+/// Split into `(sgr_params, following_text)` chunks. `sgr_params` is the
+/// parameter string of an `ESC[…m` sequence preceding the text, if any;
+/// non-SGR escapes are consumed and yield `None`.
+fn split_escapes(line: &str) -> impl Iterator<Item = (Option<&str>, &str)> {
+    let mut rest = line;
+    let mut first = true;
+    std::iter::from_fn(move || {
+        if rest.is_empty() && !first {
+            return None;
+        }
+        first = false;
+
+        let mut sgr = None;
+        if let Some(after) = rest.strip_prefix('\x1b') {
+            if let Some(csi) = after.strip_prefix('[') {
+                // CSI: parameters, then a final byte in @..~
+                let end = csi
+                    .find(|c: char| ('@'..='~').contains(&c))
+                    .unwrap_or(csi.len());
+                if csi[end..].starts_with('m') {
+                    sgr = Some(&csi[..end]);
+                }
+                rest = csi.get(end + 1..).unwrap_or("");
+            } else {
+                // two-byte escape; skip the following char
+                let mut chars = after.chars();
+                chars.next();
+                rest = chars.as_str();
+            }
+        }
+
+        let end = rest.find('\x1b').unwrap_or(rest.len());
+        let (text, tail) = rest.split_at(end);
+        rest = tail;
+        Some((sgr, text))
+    })
+}
+
+fn apply_sgr(style: &mut Style, params: &str) {
+    let codes: Vec<u32> = if params.is_empty() {
+        vec![0]
+    } else {
+        params.split(';').map(|p| p.parse().unwrap_or(0)).collect()
+    };
+
+    let mut it = codes.into_iter();
+    while let Some(code) = it.next() {
+        match code {
+            0 => *style = Style::default(),
+            1 => style.bold = true,
+            22 => style.bold = false,
+            30..=37 => style.fg = Some(PALETTE[(code - 30) as usize]),
+            90..=97 => style.fg = Some(PALETTE[(code - 90 + 8) as usize]),
+            39 => style.fg = None,
+            38 => match it.next() {
+                Some(5) => style.fg = it.next().map(color_256),
+                Some(2) => {
+                    let (r, g, b) = (it.next(), it.next(), it.next());
+                    if let (Some(r), Some(g), Some(b)) = (r, g, b) {
+                        style.fg = Some(Color32::from_rgb(r as u8, g as u8, b as u8));
+                    }
+                }
+                _ => {}
+            },
+            // backgrounds: consume their arguments, but don't paint them
+            48 => match it.next() {
+                Some(5) => {
+                    it.next();
+                }
+                Some(2) => {
+                    it.nth(2);
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+}
+
+/// xterm 256-color index to RGB.
+fn color_256(n: u32) -> Color32 {
+    match n {
+        0..=15 => PALETTE[n as usize],
+        16..=231 => {
+            let n = n - 16;
+            let level = |v: u32| if v == 0 { 0 } else { (55 + v * 40) as u8 };
+            Color32::from_rgb(level(n / 36), level((n / 6) % 6), level(n % 6))
+        }
+        _ => {
+            let g = (8 + (n.min(255) - 232) * 10) as u8;
+            Color32::from_rgb(g, g, g)
+        }
+    }
+}
@@ -1,17 +1,24 @@
+use crate::ansi;
 use crate::build_meta;
 
-use crate::build::{BuildJob, BuildResult, BuildWorker};
-use crate::fs_watcher::WatchWorker;
-use crate::preview::PreviewHandle;
-use crate::project::{AttachedScript, CompData, ProjectState, SceneDoc};
-use crossbeam::channel::{Receiver, Sender, unbounded};
+use crate::build::{self, BuildJob, BuildResult, BuildWorker};
+use crate::external;
+use crate::fs_watcher::{FsChange, SharedWatchSettings, WatchSettings, WatchWorker};
+use crate::preview::{EmbeddedPreview, PreviewFrame, PreviewHandle, PreviewStatus, TopDownView};
+use crate::project::{
+    self, AttachedScript, COMPONENT_TYPES, CompData, ComponentDoc, Diagnostic, GRID_SPACINGS,
+    MIN_MESH_DIM, ProjectConfig, ProjectState, SceneDoc, WorldBounds,
+};
+use crate::runner::{self, RunHandle, RunnerMsg};
+use crossbeam::channel::{Receiver, Sender, TryRecvError, unbounded};
 use eframe::egui;
 use eframe::egui::{ComboBox, DragValue, Rgba};
 use egui::color_picker::Alpha;
 use serde::{Deserialize, Serialize};
-use std::io::{BufRead, BufReader};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 use std::path::PathBuf;
-use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 struct Schema {
@@ -23,6 +30,8 @@ struct ScriptMeta {
     name: String,
     rust_symbol: String,
     params: Vec<ParamMeta>,
+    #[serde(default)]
+    source: Option<String>, // defining file, relative to the project root
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,63 +53,422 @@ enum ParamType {
     ColorRgba,
 }
 
+impl Schema {
+    fn script_names(&self) -> Vec<&str> {
+        self.scripts.iter().map(|s| s.name.as_str()).collect()
+    }
+
+    /// Errors among `diagnostics` by the name of the script whose source
+    /// file they point into. Diagnostics no script claims are left out.
+    fn script_errors<'a>(
+        &'a self,
+        root: &std::path::Path,
+        diagnostics: &'a [Diagnostic],
+    ) -> BTreeMap<&'a str, Vec<&'a Diagnostic>> {
+        let mut out: BTreeMap<&str, Vec<&Diagnostic>> = BTreeMap::new();
+        for d in diagnostics.iter().filter(|d| d.msg.starts_with("[error]")) {
+            let file = d.file.strip_prefix(root).unwrap_or(&d.file);
+            for s in &self.scripts {
+                if s.source_candidates().iter().any(|c| c == file) {
+                    out.entry(s.name.as_str()).or_default().push(d);
+                }
+            }
+        }
+        out
+    }
+}
+
+impl ScriptMeta {
+    /// Files that may define this script, relative to the project root: the
+    /// schema's `source` when the exporter wrote one, else guessed from the
+    /// module path of `rust_symbol`, e.g. `crate::scripts::spin::Spin` →
+    /// `src/scripts/spin.rs` or `src/scripts/spin/mod.rs`. The first path
+    /// segment may be the crate's name, so paths without it are tried too.
+    fn source_candidates(&self) -> Vec<PathBuf> {
+        if let Some(src) = &self.source {
+            return vec![PathBuf::from(src)];
+        }
+        let mut module: Vec<&str> = self.rust_symbol.split("::").collect();
+        module.pop(); // the item itself
+        let mut candidates = Vec::new();
+        let mut add = |module: &[&str]| {
+            // items in the crate root: main.rs/lib.rs is too broad to blame
+            if module.is_empty() {
+                return;
+            }
+            let dir: PathBuf = std::iter::once("src")
+                .chain(module.iter().copied())
+                .collect();
+            candidates.push(dir.with_extension("rs"));
+            candidates.push(dir.join("mod.rs"));
+        };
+        match module.split_first() {
+            Some((&"crate", rest)) => add(rest),
+            Some((_, rest)) => {
+                add(&module);
+                add(rest);
+            }
+            None => {}
+        }
+        candidates
+    }
+
+    /// A fresh attachment of this script with its default params.
+    fn attach(&self) -> AttachedScript {
+        AttachedScript {
+            name: self.name.clone(),
+            params: self.default_params(),
+        }
+    }
+
+    /// Initial params for a freshly attached script: each schema default, or
+    /// the type's zero value when there is none or it doesn't parse.
+    fn default_params(&self) -> std::collections::BTreeMap<String, ron::Value> {
+        self.params
+            .iter()
+            .map(|p| {
+                let parsed = p.default.as_deref().and_then(|d| p.ty.parse(d));
+                (p.key.clone(), parsed.unwrap_or_else(|| p.ty.zero()))
+            })
+            .collect()
+    }
+}
+
+impl ParamType {
+    /// Parse a schema default such as `true`, `3`, `0.5`, `"name"`,
+    /// `(1, 2, 3)` or `[1, 1, 1, 1]`.
+    fn parse(&self, text: &str) -> Option<ron::Value> {
+        let text = text.trim();
+        let floats = |n: usize| {
+            let inner = text.trim_matches(|c| matches!(c, '(' | ')' | '[' | ']'));
+            let v: Vec<ron::Value> = inner
+                .split(',')
+                .map(|x| x.trim().parse::<f64>().map(ron::Value::from))
+                .collect::<Result<_, _>>()
+                .ok()?;
+            (v.len() == n).then_some(ron::Value::Seq(v))
+        };
+        match self {
+            ParamType::Bool => text.parse::<bool>().ok().map(ron::Value::from),
+            ParamType::I64 => text.parse::<i64>().ok().map(ron::Value::from),
+            ParamType::F64 => text.parse::<f64>().ok().map(ron::Value::from),
+            ParamType::String => {
+                let unquoted = text
+                    .strip_prefix('"')
+                    .and_then(|t| t.strip_suffix('"'))
+                    .unwrap_or(text);
+                Some(ron::Value::from(unquoted))
+            }
+            ParamType::Vec3 => floats(3),
+            ParamType::ColorRgba => floats(4),
+        }
+    }
+
+    fn zero(&self) -> ron::Value {
+        match self {
+            ParamType::Bool => false.into(),
+            ParamType::I64 => 0i64.into(),
+            ParamType::F64 => 0.0f64.into(),
+            ParamType::String => String::new().into(),
+            ParamType::Vec3 => vec![0.0f64; 3].into(),
+            ParamType::ColorRgba => vec![1.0f64; 4].into(), // opaque white
+        }
+    }
+}
+
+/// Answer to a "you have unsaved changes" prompt.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum UnsavedChoice {
+    Save,
+    Discard,
+    Cancel,
+}
+
 pub struct EditorApp {
     project: Option<ProjectState>,
     build_tx: Sender<BuildJob>,
     build_rx: Receiver<BuildResult>,
+    release_build: Arc<AtomicBool>, // shared with the FS relay so auto-checks follow the toggle
+    build_running: Option<String>,  // command of the job in flight, from BuildResult::Started
     watcher: Option<WatchWorker>,
+    fs_changes: Option<Receiver<FsChange>>, // debounced watcher events, for hot-reload
+    watch_relay: Option<std::thread::JoinHandle<()>>, // watcher events -> auto-check; see `stop_watching`
+    watch_settings: SharedWatchSettings,              // persisted under WATCH_SETTINGS_KEY
     last_log: String,
     selected_entity: Option<usize>,
+    extra_selected: BTreeSet<usize>, // Ctrl-click additions; see `selection`
+    nudge: (f32, f32, f32),          // multi-selection "move by" offset
+    rename: Option<(usize, String)>, // (entity, edit buffer) while the id field has focus
+    rename_error: Option<String>,
+    prefabs: Vec<String>, // names in design/prefabs, rescanned when it changes
+    prefab_name: String,  // "save as prefab" edit buffer
 
     // --- runner state ---
-    run_child: Option<Child>,
-    run_rx: Option<Receiver<String>>,
+    check_before_run: bool,    // persisted under CHECK_BEFORE_RUN_KEY
+    run_gate: Option<RunGate>, // a run waiting on its check
+    run_bin: Option<String>,   // None = cargo's default bin
+    run_features: Vec<String>, // from the parsed Cargo.toml
+    run_options: BTreeMap<PathBuf, RunOptions>, // by project root; persisted under RUN_OPTIONS_KEY
+    run_child: Option<RunHandle>,
+    run_rx: Option<Receiver<RunnerMsg>>,
     run_log: Vec<String>,
+    console_filter: String, // substring filter for display only
 
     // Push-based wakeups
     egui_ctx: egui::Context,
-    preview: Option<(PreviewHandle, Sender<SceneDoc>)>,
+    preview: Option<(PreviewHandle, Sender<SceneDoc>, Sender<Option<TopDownView>>)>,
+    embedded: Option<Embedded3d>,
+    preview_sent: Option<u64>, // ProjectState::revision last sent to the preview(s)
+    preview_top_down: bool,    // preview camera mirrors the 2D view
+    preview_view_sent: Option<Option<TopDownView>>,
+    preview_sent_at: std::time::Instant,
+    thumbnail_rx: Option<Receiver<anyhow::Result<PathBuf>>>, // render_thumbnail in flight
 
     // --- viewport (2D top-down preview) ---
-    view_offset: egui::Vec2, // world-space pan (in "meters")
-    view_zoom: f32,          // screen pixels per world unit
+    viewport: Viewport,
     //
     script_schema: Option<Schema>,
-    schema_mtime: Option<std::time::SystemTime>,
+
+    // --- unsaved-changes guard ---
+    window_title: String,
+    close_prompt: bool,            // showing the save/discard/cancel modal
+    pending_open: Option<PathBuf>, // project to open once the unsaved-changes prompt is answered
+    recovery_prompt: bool,         // a newer autosave was found on open
+
+    // --- autosave ---
+    autosave_enabled: bool,
+    autosave_secs: u64,
+    last_autosave: std::time::Instant,
+    allow_close: bool, // user already answered; let the next close through
+
+    theme: egui::ThemePreference, // persisted under THEME_KEY
+    layout: PanelLayout,          // persisted under LAYOUT_KEY
+    show_shortcuts: bool,
+    show_settings: bool,
+    project_settings: Option<ProjectConfig>, // edit buffer while "Project Settings" is open
+    project_settings_error: Option<String>,
+    cargo_add_rx: Option<Receiver<RunnerMsg>>, // `cargo add bevy@…` output
+    vec3_clipboard: Option<(f32, f32, f32)>,   // inspector vector copy/paste
+    component_clipboard: Option<ComponentDoc>, // inspector component copy/paste
+    // (entity, undo depth) of the inspector field drag/typing in progress;
+    // its later frames join the undo step pushed on the first
+    inspector_edit: Option<(usize, usize)>,
+
+    // --- status bar ---
+    last_build: Option<BuildStat>,
+    build_history: VecDeque<u128>, // durations (ms) of the last BUILD_HISTORY_LEN builds
+}
+
+/// Summary of the last finished build, for the status bar.
+struct BuildStat {
+    command: String,
+    duration_ms: u128,
+    ok: bool,
+    errors: usize,
+    warnings: usize,
+}
+
+const BUILD_HISTORY_LEN: usize = 20;
+
+/// Hierarchy badges for the component types worth spotting at a glance.
+const COMPONENT_BADGES: [(&str, &str); 3] =
+    [("Mesh3d", "📦"), ("PointLight", "💡"), ("Camera3d", "📷")];
+
+/// Key bindings handled by `handle_shortcuts`, for the help window.
+const SHORTCUTS: &[(&str, &str)] = &[
+    ("Ctrl+S", "Save scene"),
+    ("Ctrl+Z", "Undo"),
+    ("Ctrl+Shift+Z", "Redo"),
+    ("Ctrl+D", "Duplicate selected entity"),
+    ("Ctrl+C / Ctrl+V", "Copy / paste entity"),
+    ("Delete", "Delete selected entity"),
+    ("F", "Frame selection in the viewport"),
+    ("W / E / R", "Move / rotate / scale gizmo"),
+    ("M", "Ruler (Shift constrains, Esc clears)"),
+    ("F5", "Run"),
+    ("Shift+F5", "Stop"),
+];
+
+const THEME_KEY: &str = "theme";
+const WATCH_SETTINGS_KEY: &str = "watch_settings";
+const CHECK_BEFORE_RUN_KEY: &str = "check_before_run";
+const LAYOUT_KEY: &str = "layout";
+const RUN_OPTIONS_KEY: &str = "run_options";
+
+/// Extra arguments and environment for the game started by "Run".
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct RunOptions {
+    args: String, // split on whitespace, passed after `--`
+    env: Vec<(String, String)>,
+}
+
+/// Panel sizes, restored as the panels' defaults on the next launch. Window
+/// geometry is persisted by eframe itself (`NativeOptions::persist_window`).
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+struct PanelLayout {
+    hierarchy_width: f32,
+    inspector_width: f32,
+    console_height: f32,
+}
+
+impl Default for PanelLayout {
+    fn default() -> Self {
+        Self {
+            hierarchy_width: 240.0,
+            inspector_width: 360.0,
+            console_height: 160.0,
+        }
+    }
+}
+
+/// The 3D preview rendered offscreen and shown above the 2D view.
+struct Embedded3d {
+    renderer: EmbeddedPreview, // stops on drop
+    scenes: Sender<SceneDoc>,
+    frames: Receiver<PreviewFrame>,
+    texture: Option<egui::TextureHandle>,
+}
+
+/// Status line for a preview whose Bevy app panicked, with a hint for the
+/// usual causes.
+fn preview_crash_message(what: &str, panic: &str) -> String {
+    let hint = if panic.contains("EventLoop") {
+        " (winit allows one event loop per process: restart the editor to reopen the window)"
+    } else if panic.to_lowercase().contains("adapter") {
+        " (no usable GPU adapter; try starting the editor with another WGPU_BACKEND)"
+    } else {
+        ""
+    };
+    format!("{what} crashed: {panic}{hint}")
+}
+
+/// Render size of the embedded 3D preview; scaled to fit the panel.
+const EMBEDDED_SIZE: (u32, u32) = (960, 540);
+
+/// Progress of a run that has to pass `cargo check` first.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum RunGate {
+    Queued,   // check sent, not started yet
+    Checking, // our check is running; its result decides
 }
 
 impl EditorApp {
-    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
-        let (build_tx, build_rx) = BuildWorker::start();
+    /// `report` receives every finished build as JSON (see `build::BuildReport`).
+    pub fn new(cc: &eframe::CreationContext<'_>, report: Option<build::ReportSink>) -> Self {
+        let egui_ctx = cc.egui_ctx.clone();
+        let (build_tx, build_rx) = BuildWorker::start(report, move || egui_ctx.request_repaint());
+        let theme = cc
+            .storage
+            .and_then(|s| eframe::get_value(s, THEME_KEY))
+            .unwrap_or(egui::ThemePreference::System);
+        cc.egui_ctx.set_theme(theme);
+        let watch_settings: WatchSettings = cc
+            .storage
+            .and_then(|s| eframe::get_value(s, WATCH_SETTINGS_KEY))
+            .unwrap_or_default();
+        let check_before_run = cc
+            .storage
+            .and_then(|s| eframe::get_value(s, CHECK_BEFORE_RUN_KEY))
+            .unwrap_or(false);
+        let layout = cc
+            .storage
+            .and_then(|s| eframe::get_value(s, LAYOUT_KEY))
+            .unwrap_or_default();
+        let run_options = cc
+            .storage
+            .and_then(|s| eframe::get_value(s, RUN_OPTIONS_KEY))
+            .unwrap_or_default();
         Self {
             project: None,
             build_tx,
             build_rx,
+            release_build: Arc::new(AtomicBool::new(false)),
+            build_running: None,
             watcher: None,
+            watch_relay: None,
+            fs_changes: None,
+            watch_settings: Arc::new(Mutex::new(watch_settings)),
             last_log: String::new(),
             selected_entity: None,
+            extra_selected: BTreeSet::new(),
+            nudge: (0.0, 0.0, 0.0),
+            rename: None,
+            rename_error: None,
+            prefabs: Vec::new(),
+            prefab_name: String::new(),
 
+            check_before_run,
+            run_gate: None,
+            run_bin: None,
+            run_features: Vec::new(),
+            run_options,
             run_child: None,
             run_rx: None,
             run_log: Vec::new(),
+            console_filter: String::new(),
 
             egui_ctx: cc.egui_ctx.clone(),
             preview: None,
+            embedded: None,
+            preview_sent: None,
+            preview_top_down: false,
+            preview_view_sent: None,
+            preview_sent_at: std::time::Instant::now(),
+            thumbnail_rx: None,
 
-            view_offset: egui::vec2(0.0, 0.0),
-            view_zoom: 40.0,
+            viewport: Viewport {
+                offset: egui::vec2(0.0, 0.0),
+                zoom: 40.0,
+                focus_requested: false,
+                snap_enabled: false,
+                snap_step: 0.25,
+                depth_cue: DepthCue::default(),
+                show_minimap: true,
+                show_ids: false,
+                plane: ViewPlane::default(),
+                gizmo_mode: GizmoMode::Move,
+                ruler: None,
+                drag: None,
+            },
             script_schema: None,
-            schema_mtime: None,
+
+            window_title: "Bevy Editor".into(),
+            close_prompt: false,
+            pending_open: None,
+            recovery_prompt: false,
+
+            autosave_enabled: false,
+            autosave_secs: 60,
+            last_autosave: std::time::Instant::now(),
+            allow_close: false,
+
+            theme,
+            layout,
+            show_shortcuts: false,
+            show_settings: false,
+            project_settings: None,
+            project_settings_error: None,
+            cargo_add_rx: None,
+            vec3_clipboard: None,
+            component_clipboard: None,
+            inspector_edit: None,
+
+            last_build: None,
+            build_history: VecDeque::with_capacity(BUILD_HISTORY_LEN),
         }
     }
     fn draw_scripts_section(
         ui: &mut egui::Ui,
-        ent: &mut crate::project::EntityDoc,
+        idx: usize,
+        ent: &crate::project::EntityDoc,
         schema: Option<&Schema>,
-    ) {
+    ) -> Option<BulkOp> {
+        let mut op = None;
         ui.separator();
         ui.collapsing("Scripts", |ui| {
-            let scripts_vec = &mut ent.scripts;
+            let scripts_vec = &ent.scripts;
 
             if let Some(schema) = schema {
                 ui.horizontal(|ui| {
@@ -124,15 +492,12 @@ impl EditorApp {
                         PICK = pick.min(max);
                     }
 
-                    if ui.button("Add").clicked() {
-                        if let Some(sel) = names.get(pick) {
-                            let already = scripts_vec.iter().any(|a| a.name == *sel);
-                            if !already {
-                                scripts_vec.push(AttachedScript {
-                                    name: (*sel).to_string(),
-                                    params: Default::default(),
-                                });
-                            }
+                    if ui.button("Add").clicked()
+                        && let Some(sel) = names.get(pick)
+                    {
+                        let already = scripts_vec.iter().any(|a| a.name == *sel);
+                        if !already {
+                            op = Some(BulkOp::AttachScript(idx, schema.scripts[pick].attach()));
                         }
                     }
                 });
@@ -144,7 +509,7 @@ impl EditorApp {
 
             // current attachments
             let mut to_remove: Option<usize> = None;
-            for (i, a) in scripts_vec.iter_mut().enumerate() {
+            for (i, a) in scripts_vec.iter().enumerate() {
                 ui.group(|ui| {
                     ui.horizontal(|ui| {
                         ui.label(format!("• {}", a.name));
@@ -159,9 +524,10 @@ impl EditorApp {
                 ui.add_space(4.0);
             }
             if let Some(i) = to_remove {
-                scripts_vec.remove(i);
+                op = Some(BulkOp::DetachScript(idx, i));
             }
         });
+        op
     }
 
     fn load_script_schema_from(&mut self, root: &std::path::Path) {
@@ -171,10 +537,21 @@ impl EditorApp {
         match fs::read_to_string(&path) {
             Ok(txt) => match ron::from_str::<Schema>(&txt) {
                 Ok(schema) => {
-                    self.schema_mtime = fs::metadata(&path).ok().and_then(|m| m.modified().ok());
                     let count = schema.scripts.len();
+                    let orphans = self
+                        .project
+                        .as_ref()
+                        .and_then(|p| p.design_scene.as_ref())
+                        .map_or(0, |s| s.orphaned_scripts(&schema.script_names()).len());
                     self.script_schema = Some(schema);
-                    self.last_log = format!("Loaded script schema ({} scripts).", count);
+                    self.last_log = if orphans == 0 {
+                        format!("Loaded script schema ({} scripts).", count)
+                    } else {
+                        format!(
+                            "Loaded script schema ({count} scripts); {orphans} attached script(s) \
+                             no longer in it, see Validation."
+                        )
+                    };
                 }
                 Err(e) => {
                     self.script_schema = None;
@@ -190,44 +567,185 @@ impl EditorApp {
         self.egui_ctx.request_repaint();
     }
 
-    // button to open/ensure preview (reserved for future Bevy offscreen):
     fn ensure_preview(&mut self) {
         if self.preview.is_none() {
             let (tx, rx) = unbounded::<SceneDoc>();
-            let handle = PreviewHandle::start(rx);
-            self.preview = Some((handle, tx));
+            let (view_tx, view_rx) = unbounded();
+            let egui_ctx = self.egui_ctx.clone();
+            let handle = PreviewHandle::start(rx, view_rx, move || egui_ctx.request_repaint());
+            self.preview = Some((handle, tx, view_tx));
+            self.preview_sent = None; // new window needs the current scene
+            self.preview_view_sent = None;
+        }
+    }
+
+    /// Drop preview handles whose Bevy thread has ended, saying why.
+    fn poll_preview_exit(&mut self) {
+        let ended = |s: PreviewStatus| match s {
+            PreviewStatus::Running => None,
+            PreviewStatus::Exited => Some(None),
+            PreviewStatus::Crashed(msg) => Some(Some(msg)),
+        };
+        if let Some(crash) = self.preview.as_ref().and_then(|(h, ..)| ended(h.status())) {
+            self.preview = None;
+            self.last_log = match crash {
+                None => "preview window closed".into(),
+                Some(msg) => preview_crash_message("preview window", &msg),
+            };
+        }
+        // the embedded renderer only exits on its own by crashing
+        if let Some(Some(msg)) = self
+            .embedded
+            .as_ref()
+            .and_then(|e| ended(e.renderer.status()))
+        {
+            self.embedded = None;
+            self.last_log = preview_crash_message("embedded preview", &msg);
+        }
+    }
+
+    /// Start or stop the 3D preview embedded in the central panel.
+    fn toggle_embedded_preview(&mut self) {
+        if self.embedded.take().is_some() {
+            return;
+        }
+        let (tx, rx) = unbounded::<SceneDoc>();
+        let egui_ctx = self.egui_ctx.clone();
+        let (renderer, frames) =
+            EmbeddedPreview::start(rx, EMBEDDED_SIZE, move || egui_ctx.request_repaint());
+        self.embedded = Some(Embedded3d {
+            renderer,
+            scenes: tx,
+            frames,
+            texture: None,
+        });
+        self.preview_sent = None; // the new renderer needs the current scene
+    }
+
+    /// Send the scene to the preview window and the embedded preview when it
+    /// changed, at most ~10 times a second. Changes are detected by the
+    /// revision counter `ProjectState::scene_changed` bumps, so every edit
+    /// path (inspector, viewport drag, reload) is covered.
+    fn sync_preview(&mut self, ctx: &egui::Context) {
+        const MIN_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+        if self.preview.is_none() && self.embedded.is_none() {
+            return;
+        }
+        if let Some((_, _, view_tx)) = &self.preview {
+            // the 2D view's framing is cheap to compare, so it goes out every change
+            let view = (self.preview_top_down && self.viewport.plane == ViewPlane::Top).then_some(
+                TopDownView {
+                    center: (self.viewport.offset.x, self.viewport.offset.y),
+                    zoom: self.viewport.zoom,
+                },
+            );
+            if self.preview_view_sent != Some(view) && view_tx.send(view).is_ok() {
+                self.preview_view_sent = Some(view);
+            }
+        }
+        let Some(p) = &self.project else {
+            return;
+        };
+        if p.design_scene.is_none() || self.preview_sent == Some(p.revision) {
+            return;
         }
+        let since = self.preview_sent_at.elapsed();
+        if since < MIN_INTERVAL {
+            ctx.request_repaint_after(MIN_INTERVAL - since); // come back for the trailing edit
+            return;
+        }
+        // the preview doesn't know the project root; hand it absolute asset paths
+        let Some(scene) = p.scene_for_preview() else {
+            return;
+        };
+        if let Some((_, tx, _)) = &self.preview
+            && tx.send(scene.clone()).is_err()
+        {
+            self.preview = None;
+            self.last_log = "preview window closed".into();
+        }
+        if let Some(e) = &self.embedded
+            && e.scenes.send(scene).is_err()
+        {
+            self.embedded = None;
+            self.last_log = "embedded preview stopped".into();
+        }
+        self.preview_sent = Some(p.revision);
+        self.preview_sent_at = std::time::Instant::now();
     }
 
     fn open_project(&mut self, path: PathBuf) {
         match ProjectState::open(&path) {
             Ok(proj) => {
-                // Initial check
-                let _ = self.build_tx.send(BuildJob::Check {
-                    root: proj.root.clone(),
-                });
+                // Stop the old project's watcher before anything can fire for it
+                self.stop_watching();
+                self.selected_entity = None;
+                self.extra_selected.clear();
+                self.viewport.drag = None;
+
+                // Initial check (or clippy, like the auto-runs)
+                if proj.has_manifest() {
+                    let _ = self.build_tx.send(BuildJob::lint(
+                        proj.root.clone(),
+                        self.release_build.load(Ordering::Relaxed),
+                        self.watch_settings.lock().unwrap().clippy,
+                    ));
+                }
                 self.egui_ctx.request_repaint();
 
                 // Watcher -> build loop
                 let (evt_tx, evt_rx) = unbounded();
-                self.watcher = Some(WatchWorker::start(proj.root.clone(), evt_tx));
+                match WatchWorker::start(proj.root.clone(), self.watch_settings.clone(), evt_tx) {
+                    Ok(w) => self.watcher = Some(w),
+                    Err(e) => self.last_log = format!("File watching unavailable: {e}"),
+                }
 
                 let build_tx = self.build_tx.clone();
                 let root = proj.root.clone(); // avoid partially moving proj
+                let release = self.release_build.clone();
+                let settings = self.watch_settings.clone();
                 let egui_ctx = self.egui_ctx.clone();
-                std::thread::spawn(move || {
-                    while let Ok(_evt) = evt_rx.recv() {
-                        let _ = build_tx.send(BuildJob::Check { root: root.clone() });
-                        egui_ctx.request_repaint(); // wake UI when FS events arrive
+                let (changes_tx, changes_rx) = unbounded();
+                self.fs_changes = Some(changes_rx);
+                // ends once the watcher stops and drops its sender
+                self.watch_relay = Some(std::thread::spawn(move || {
+                    while let Ok(change) = evt_rx.recv() {
+                        let WatchSettings {
+                            auto_check, clippy, ..
+                        } = *settings.lock().unwrap();
+                        // design/ edits don't affect the build; code and manifests do
+                        let affects_build = !matches!(change, FsChange::SceneChanged(_));
+                        if affects_build && auto_check && root.join("Cargo.toml").is_file() {
+                            let _ = build_tx.send(BuildJob::lint(
+                                root.clone(),
+                                release.load(Ordering::Relaxed),
+                                clippy,
+                            ));
+                        }
+                        // hot-reload runs either way
+                        let _ = changes_tx.send(change);
+                        egui_ctx.request_repaint();
                     }
-                });
+                }));
+
+                // Offer to recover edits a crash left in the autosave file
+                self.recovery_prompt = proj.pending_autosave().is_some();
 
                 // Set the project
                 self.project = Some(proj);
+                self.preview_sent = None; // revisions restart with each project
 
                 // ⬅️ Borrow ends; now take a plain PathBuf and call the &mut self method.
                 let root_for_schema = self.project.as_ref().unwrap().root.clone();
                 self.load_script_schema_from(&root_for_schema);
+
+                self.prefabs = self.project.as_ref().unwrap().list_prefabs();
+
+                // Discover bins/features for the run controls
+                self.run_bin = None;
+                self.run_features.clear();
+                self.refresh_cargo_info();
             }
             Err(e) => {
                 self.last_log = format!("Failed to open project: {e:?}");
@@ -236,6 +754,32 @@ impl EditorApp {
         }
     }
 
+    /// Re-read Cargo.toml for the run controls, keeping the bin and feature
+    /// picks that still exist.
+    fn refresh_cargo_info(&mut self) {
+        let Some(p) = &mut self.project else {
+            return;
+        };
+        p.cargo = match project::read_cargo_manifest(&p.root) {
+            Ok(info) => Some(info),
+            Err(e) => {
+                self.last_log = format!("Couldn't read Cargo.toml: {e:#}");
+                None
+            }
+        };
+        let bins: Vec<&str> = p.cargo.as_ref().map(|c| c.bins()).unwrap_or_default();
+        if self.run_bin.as_deref().is_some_and(|b| !bins.contains(&b)) {
+            self.run_bin = None;
+        }
+        let features: Vec<&String> = p
+            .cargo
+            .iter()
+            .flat_map(|c| &c.packages)
+            .flat_map(|pkg| &pkg.features)
+            .collect();
+        self.run_features.retain(|f| features.contains(&f));
+    }
+
     fn ui_menubar(&mut self, ui: &mut egui::Ui) {
         egui::MenuBar::new().ui(ui, |ui| {
             ui.menu_button("File", |ui| {
@@ -245,7 +789,11 @@ impl EditorApp {
                 }
                 if ui.button("Open Project…").clicked() {
                     if let Some(path) = rfd::FileDialog::new().pick_folder() {
-                        self.open_project(path);
+                        if self.is_dirty() {
+                            self.pending_open = Some(path);
+                        } else {
+                            self.open_project(path);
+                        }
                     }
                     ui.close();
                 }
@@ -253,20 +801,96 @@ impl EditorApp {
                     .add_enabled(self.project.is_some(), egui::Button::new("Save Scene"))
                     .clicked()
                 {
-                    if let Some(p) = &mut self.project {
-                        match p.save_design() {
-                            Ok(_) => self.last_log = "scene saved".into(),
-                            Err(e) => self.last_log = format!("save failed: {e:#}"),
-                        }
-                    }
-                    self.egui_ctx.request_repaint();
+                    self.save_scene();
+                    ui.close();
+                }
+                if ui
+                    .add_enabled(self.project.is_some(), egui::Button::new("Save Scene As…"))
+                    .clicked()
+                {
+                    ui.close();
+                    self.save_scene_as();
+                }
+                if ui
+                    .add_enabled(
+                        self.thumbnail_rx.is_none()
+                            && self
+                                .project
+                                .as_ref()
+                                .is_some_and(|p| p.design_path().is_some()),
+                        egui::Button::new("Render Thumbnail"),
+                    )
+                    .on_hover_text("Render the scene offscreen to <scene>.thumb.png next to it")
+                    .on_disabled_hover_text(if self.thumbnail_rx.is_some() {
+                        "Already rendering"
+                    } else {
+                        "Save the scene first"
+                    })
+                    .clicked()
+                {
+                    ui.close();
+                    self.start_thumbnail();
+                }
+                if ui
+                    .add_enabled(
+                        self.project.is_some(),
+                        egui::Button::new("Import Scene (JSON)…"),
+                    )
+                    .clicked()
+                {
+                    ui.close();
+                    self.import_scene_json();
+                }
+                if ui
+                    .add_enabled(
+                        self.project
+                            .as_ref()
+                            .is_some_and(|p| p.design_scene.is_some()),
+                        egui::Button::new("Export Scene (JSON)…"),
+                    )
+                    .clicked()
+                {
+                    ui.close();
+                    self.export_scene_json();
+                }
+                if ui
+                    .add_enabled(
+                        self.project
+                            .as_ref()
+                            .is_some_and(|p| p.design_path().is_some()),
+                        egui::Button::new("Export Bevy Scene…"),
+                    )
+                    .on_hover_text(
+                        "Write a Rust spawn function for this scene next to the design file",
+                    )
+                    .on_disabled_hover_text("Save the scene first")
+                    .clicked()
+                {
                     ui.close();
+                    if let Some(p) = &self.project {
+                        self.last_log = match p.export_bevy_scene() {
+                            Ok(path) => format!("exported {}", path.display()),
+                            Err(e) => format!("export failed: {e:#}"),
+                        };
+                    }
                 }
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.autosave_enabled, "Autosave every")
+                        .on_hover_text("Writes a .autosave recovery file next to the scene");
+                    ui.add_enabled(
+                        self.autosave_enabled,
+                        DragValue::new(&mut self.autosave_secs)
+                            .range(5..=3600)
+                            .suffix(" s"),
+                    );
+                });
+                let unavailable = self.cargo_unavailable();
                 if ui
                     .add_enabled(
-                        self.project.is_some() && self.run_child.is_none(),
+                        unavailable.is_none() && self.run_child.is_none(),
                         egui::Button::new("Run"),
                     )
+                    .on_disabled_hover_text(unavailable.unwrap_or("Already running"))
                     .clicked()
                 {
                     self.start_run();
@@ -279,130 +903,1460 @@ impl EditorApp {
                     self.stop_run();
                     ui.close();
                 }
+                ui.separator();
+                if ui
+                    .add_enabled(
+                        self.project.is_some(),
+                        egui::Button::new("Reveal in File Manager"),
+                    )
+                    .clicked()
+                {
+                    ui.close();
+                    if let Some(p) = &self.project
+                        && let Err(e) = external::reveal_in_file_manager(&p.root)
+                    {
+                        self.last_log = format!("couldn't open the file manager: {e}");
+                    }
+                }
+                if ui
+                    .add_enabled(
+                        self.project.is_some(),
+                        egui::Button::new("Open Terminal Here"),
+                    )
+                    .clicked()
+                {
+                    ui.close();
+                    if let Some(p) = &self.project
+                        && let Err(e) = external::open_terminal(&p.root)
+                    {
+                        self.last_log = format!("couldn't open a terminal: {e}");
+                    }
+                }
+                ui.separator();
+                if ui.button("Settings…").clicked() {
+                    self.show_settings = true;
+                    ui.close();
+                }
+                if ui
+                    .add_enabled(
+                        self.project.is_some(),
+                        egui::Button::new("Project Settings…"),
+                    )
+                    .clicked()
+                {
+                    self.project_settings = self.project.as_ref().map(|p| p.config.clone());
+                    self.project_settings_error = None;
+                    ui.close();
+                }
                 if ui.button("Exit").clicked() {
                     ui.ctx().send_viewport_cmd(egui::ViewportCommand::Close);
                 }
             });
+            ui.menu_button("Edit", |ui| {
+                let (can_undo, can_redo) = self
+                    .project
+                    .as_ref()
+                    .map_or((false, false), |p| (!p.undo.is_empty(), !p.redo.is_empty()));
+                if ui
+                    .add_enabled(can_undo, egui::Button::new("Undo").shortcut_text("Ctrl+Z"))
+                    .clicked()
+                {
+                    self.undo();
+                    ui.close();
+                }
+                if ui
+                    .add_enabled(
+                        can_redo,
+                        egui::Button::new("Redo").shortcut_text("Ctrl+Shift+Z"),
+                    )
+                    .clicked()
+                {
+                    self.redo();
+                    ui.close();
+                }
+            });
+            ui.menu_button("View", |ui| {
+                for (pref, label) in [
+                    (egui::ThemePreference::Light, "Light"),
+                    (egui::ThemePreference::Dark, "Dark"),
+                    (egui::ThemePreference::System, "Follow System"),
+                ] {
+                    if ui.radio_value(&mut self.theme, pref, label).clicked() {
+                        ui.ctx().set_theme(self.theme);
+                        ui.close();
+                    }
+                }
+                ui.separator();
+                let cue = &mut self.viewport.depth_cue;
+                ui.checkbox(&mut cue.enabled, "Depth cue").on_hover_text(
+                    "Draw higher entities larger and lower ones faded in the 2D view",
+                );
+                ui.add_enabled_ui(cue.enabled, |ui| {
+                    ui.add(
+                        egui::Slider::new(&mut cue.grow_per_unit, 0.0..=0.25).text("size / unit Y"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut cue.fade_per_unit, 0.0..=0.5)
+                            .text("fade / unit below top"),
+                    );
+                });
+                ui.label("2D view plane");
+                for (plane, label) in [
+                    (ViewPlane::Top, "Top (x/z)"),
+                    (ViewPlane::Front, "Front (x/y)"),
+                ] {
+                    if ui
+                        .radio_value(&mut self.viewport.plane, plane, label)
+                        .clicked()
+                    {
+                        self.viewport.focus_requested = true; // the old framing means nothing here
+                    }
+                }
+                ui.checkbox(&mut self.viewport.show_ids, "Entity ids")
+                    .on_hover_text("Label each shape in the 2D view with its entity id");
+                ui.checkbox(&mut self.viewport.show_minimap, "Minimap")
+                    .on_hover_text("Overview of the whole scene; click it to jump there");
+            });
+            ui.menu_button("Help", |ui| {
+                if ui.button("Shortcuts").clicked() {
+                    self.show_shortcuts = true;
+                    ui.close();
+                }
+            });
+
+            if self.is_dirty() {
+                ui.separator();
+                ui.colored_label(ui.visuals().warn_fg_color, "● unsaved");
+            }
         });
     }
 
-    // ---------- runner helpers ----------
-
-    fn start_run(&mut self) {
-        let Some(p) = &self.project else {
-            self.last_log = "no project open".into();
-            self.egui_ctx.request_repaint();
-            return;
+    /// Keep the outcome of a finished build for the status bar.
+    fn record_build(
+        &mut self,
+        command: String,
+        duration_ms: u128,
+        ok: bool,
+        diagnostics: &[Diagnostic],
+    ) {
+        let count = |level: &str| {
+            diagnostics
+                .iter()
+                .filter(|d| d.msg.starts_with(&format!("[{level}]")))
+                .count()
         };
-        if self.run_child.is_some() {
-            self.last_log = "runner already active".into();
-            self.egui_ctx.request_repaint();
-            return;
+        let errors = count("error");
+        let warnings = count("warning");
+        if self.build_history.len() == BUILD_HISTORY_LEN {
+            self.build_history.pop_front();
         }
+        self.build_history.push_back(duration_ms);
+        self.last_build = Some(BuildStat {
+            command,
+            duration_ms,
+            ok,
+            errors,
+            warnings,
+        });
+    }
 
-        let mut cmd = Command::new("cargo");
-        cmd.arg("run")
-            .current_dir(&p.root)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
+    fn ui_status_bar(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            if let Some(cmd) = &self.build_running {
+                ui.spinner();
+                ui.label(cmd);
+            } else if let Some(b) = &self.last_build {
+                let (text, color) = if b.ok {
+                    ("OK", egui::Color32::from_rgb(90, 180, 90))
+                } else {
+                    ("FAILED", ui.visuals().error_fg_color)
+                };
+                ui.label(&b.command);
+                ui.colored_label(color, text);
+                ui.label(format!("{} ms", b.duration_ms));
+                ui.separator();
+                ui.colored_label(ui.visuals().error_fg_color, format!("{} errors", b.errors));
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    format!("{} warnings", b.warnings),
+                );
+            } else {
+                ui.weak("No build yet");
+            }
+            if self.build_history.len() > 1 {
+                ui.separator();
+                draw_sparkline(ui, &self.build_history);
+            }
+        });
+    }
 
-        match cmd.spawn() {
-            Ok(mut child) => {
-                let stdout = child.stdout.take();
-                let stderr = child.stderr.take();
-                let (tx, rx) = unbounded::<String>();
+    // ---------- scene editing ----------
 
-                if let Some(out) = stdout {
-                    let tx_out = tx.clone();
-                    let egui_ctx = self.egui_ctx.clone();
-                    std::thread::spawn(move || {
-                        let reader = BufReader::new(out);
-                        for line in reader.lines().flatten() {
-                            let _ = tx_out.send(format!("[out] {line}"));
-                            egui_ctx.request_repaint(); // wake per line
-                        }
-                    });
-                }
-                if let Some(err) = stderr {
-                    let tx_err = tx.clone();
-                    let egui_ctx = self.egui_ctx.clone();
-                    std::thread::spawn(move || {
-                        let reader = BufReader::new(err);
-                        for line in reader.lines().flatten() {
-                            let _ = tx_err.send(format!("[err] {line}"));
-                            egui_ctx.request_repaint(); // wake per line
-                        }
-                    });
-                }
+    /// Every selected entity (the primary one plus Ctrl-click additions), ascending.
+    fn selection(&self) -> Vec<usize> {
+        let mut all: BTreeSet<usize> = self.extra_selected.clone();
+        all.extend(self.selected_entity);
+        all.into_iter().collect()
+    }
 
-                self.run_child = Some(child);
-                self.run_rx = Some(rx);
-                self.run_log.clear();
-                self.last_log = "runner started".into();
-                self.egui_ctx.request_repaint();
-            }
-            Err(e) => {
-                self.last_log = format!("failed to start runner: {e}");
-                self.egui_ctx.request_repaint();
-            }
-        }
+    /// The selection minus locked entities, for operations that modify them.
+    fn editable_selection(&self) -> Vec<usize> {
+        let scene = self.project.as_ref().and_then(|p| p.design_scene.as_ref());
+        self.selection()
+            .into_iter()
+            .filter(|&i| scene.is_some_and(|s| !s.entities[i].is_locked()))
+            .collect()
     }
 
-    fn stop_run(&mut self) {
-        if let Some(mut child) = self.run_child.take() {
-            let _ = child.kill();
-            let _ = child.wait();
-            self.last_log = "runner stopped".into();
+    /// Run `f` on the open scene as one edit: snapshot it first, then record
+    /// a single undo step and mark the scene edited if `f` changed anything.
+    /// None when no scene is open.
+    fn with_edit<R>(&mut self, f: impl FnOnce(&mut SceneDoc) -> R) -> Option<R> {
+        let p = self.project.as_mut()?;
+        let scene = p.design_scene.as_mut()?;
+        let before = scene.clone();
+        let out = f(scene);
+        if *scene != before {
+            p.push_undo(before);
+            p.mark_edited();
         }
-        self.run_rx = None;
-        self.egui_ctx.request_repaint();
+        Some(out)
     }
 
-    fn pump_run_log(&mut self) {
-        if let Some(rx) = &self.run_rx {
-            while let Ok(line) = rx.try_recv() {
-                self.run_log.push(line);
-                if self.run_log.len() > 5000 {
-                    let drain = self.run_log.len() - 5000;
-                    self.run_log.drain(0..drain);
-                }
+    /// Duplicate every selected entity; the copies become the selection.
+    fn duplicate_selected(&mut self) {
+        let selection = self.selection();
+        let Some((copies, last_id)) = self.with_edit(|scene| {
+            let copies: Vec<usize> = selection
+                .iter()
+                .filter_map(|&i| scene.duplicate_entity(i, 0.5))
+                .collect();
+            let last_id = copies.last().map(|&i| scene.entities[i].id.clone());
+            (copies, last_id)
+        }) else {
+            return;
+        };
+        let Some((&last, rest)) = copies.split_last() else {
+            return;
+        };
+        self.last_log = match rest {
+            [] => format!("duplicated as {}", last_id.unwrap_or_default()),
+            _ => format!("duplicated {} entities", copies.len()),
+        };
+        self.selected_entity = Some(last);
+        self.extra_selected = rest.iter().copied().collect();
+    }
+
+    /// Delete every selected entity as one undo step.
+    fn delete_selected(&mut self) {
+        let selection = self.selection();
+        if let [one] = selection[..] {
+            self.delete_entity(one);
+            return;
+        }
+        let all = selection.len();
+        let selection = self.editable_selection();
+        // back to front so the remaining indices stay valid
+        let removed = self
+            .with_edit(|scene| {
+                selection
+                    .iter()
+                    .rev()
+                    .filter_map(|&i| scene.remove_entity(i))
+                    .count()
+            })
+            .unwrap_or(0);
+        let skipped = all - selection.len();
+        if skipped > 0 {
+            self.last_log = format!("deleted {removed} entities; {skipped} locked");
+        } else {
+            self.last_log = format!("deleted {removed} entities");
+        }
+        if removed == 0 {
+            return;
+        }
+        self.selected_entity = None;
+        self.extra_selected.clear();
+        self.rename = None;
+        self.rename_error = None;
+        self.viewport.drag = None;
+    }
+
+    /// Shift every selected entity that has a Transform by `self.nudge`.
+    fn nudge_selected(&mut self) {
+        let selection = self.editable_selection();
+        let (dx, dy, dz) = self.nudge;
+        if self.nudge == (0.0, 0.0, 0.0) {
+            return;
+        }
+        let moved = self.with_edit(|scene| {
+            let mut moved = 0;
+            for i in selection {
+                if let Some(t) = scene.entities[i].component_mut("Transform") {
+                    let (x, y, z) = t.data.translation.unwrap_or((0.0, 0.0, 0.0));
+                    t.data.translation = Some((x + dx, y + dy, z + dz));
+                    moved += 1;
+                }
+            }
+            moved
+        });
+        if let Some(moved @ 1..) = moved {
+            self.last_log = format!("moved {moved} entities");
+        }
+    }
+
+    /// Put the Transform of entity `idx` back to the origin, unrotated and
+    /// unscaled.
+    fn reset_transform_of(&mut self, idx: usize) {
+        self.with_edit(|scene| {
+            if let Some(c) = scene
+                .entities
+                .get_mut(idx)
+                .and_then(|e| e.component_mut("Transform"))
+            {
+                reset_transform(&mut c.data);
+            }
+        });
+    }
+
+    /// Run `f` on entity `idx` as one undo step.
+    fn edit_entity(&mut self, idx: usize, f: impl FnOnce(&mut crate::project::EntityDoc)) {
+        self.with_edit(|scene| {
+            if let Some(ent) = scene.entities.get_mut(idx) {
+                f(ent);
+            }
+        });
+    }
+
+    /// Apply a rename typed in the inspector, or show why it was refused.
+    fn rename_entity(&mut self, idx: usize, id: &str) {
+        self.rename_error = match self.with_edit(|scene| scene.rename_entity(idx, id)) {
+            Some(Err(e)) => Some(format!("{e}")),
+            _ => None,
+        };
+    }
+
+    /// Paste the component clipboard onto every selected entity, replacing
+    /// its component of that type or adding it.
+    fn paste_component_on_selection(&mut self) {
+        let Some(pasted) = self.component_clipboard.clone() else {
+            return;
+        };
+        let selection = self.editable_selection();
+        self.with_edit(|scene| {
+            for i in selection {
+                let ent = &mut scene.entities[i];
+                match ent.component_mut(&pasted.type_id) {
+                    Some(c) => c.data = pasted.data.clone(),
+                    None => ent.components.push(pasted.clone()),
+                }
+            }
+        });
+    }
+
+    /// Line the selection up on `axis` at the group's min, center or max.
+    fn align_selected(&mut self, axis: Axis, to: AlignTo) {
+        self.edit_selected_translations(axis, "aligned", |coords| {
+            let (min, max) = min_max(coords);
+            let target = match to {
+                AlignTo::Min => min,
+                AlignTo::Center => (min + max) * 0.5,
+                AlignTo::Max => max,
+            };
+            coords.iter_mut().for_each(|c| *c = target);
+        });
+    }
+
+    /// Space the selection evenly on `axis` between its outermost entities,
+    /// keeping their order.
+    fn distribute_selected(&mut self, axis: Axis) {
+        self.edit_selected_translations(axis, "distributed", |coords| {
+            if coords.len() < 3 {
+                return;
+            }
+            let (min, max) = min_max(coords);
+            let mut order: Vec<usize> = (0..coords.len()).collect();
+            order.sort_by(|&a, &b| coords[a].total_cmp(&coords[b]));
+            let step = (max - min) / (coords.len() - 1) as f32;
+            for (rank, i) in order.into_iter().enumerate() {
+                coords[i] = min + step * rank as f32;
+            }
+        });
+    }
+
+    /// Run `f` over the selected Transforms' `axis` coordinates (snapped
+    /// afterwards while snapping is on) as one undo step.
+    fn edit_selected_translations(&mut self, axis: Axis, verb: &str, f: impl FnOnce(&mut [f32])) {
+        let selection = self.editable_selection();
+        let snap = self.viewport.snap();
+        let Some(changed) = self.with_edit(|scene| {
+            let pick = |t: (f32, f32, f32)| match axis {
+                Axis::X => t.0,
+                Axis::Z => t.2,
+            };
+            let targets: Vec<usize> = selection
+                .into_iter()
+                .filter(|&i| scene.entities[i].component("Transform").is_some())
+                .collect();
+            let translation = |scene: &SceneDoc, i: usize| {
+                scene.entities[i]
+                    .component("Transform")
+                    .and_then(|c| c.data.translation)
+                    .unwrap_or((0.0, 0.0, 0.0))
+            };
+            let mut coords: Vec<f32> = targets
+                .iter()
+                .map(|&i| pick(translation(scene, i)))
+                .collect();
+            if coords.len() < 2 {
+                return None;
+            }
+            f(&mut coords);
+
+            let mut changed = 0;
+            for (&i, mut v) in targets.iter().zip(coords) {
+                if let Some(step) = snap {
+                    v = snap_to(v, step);
+                }
+                let mut t = translation(scene, i);
+                match axis {
+                    Axis::X => t.0 = v,
+                    Axis::Z => t.2 = v,
+                }
+                let data = &mut scene.entities[i]
+                    .component_mut("Transform")
+                    .expect("filtered above")
+                    .data;
+                if data.translation != Some(t) {
+                    data.translation = Some(t);
+                    changed += 1;
+                }
+            }
+            Some(changed)
+        }) else {
+            return;
+        };
+        self.last_log = match changed {
+            Some(changed) => format!("{verb} {changed} entities"),
+            None => "select at least two entities with a Transform".into(),
+        };
+    }
+
+    /// Keyboard bindings for the common commands (listed in `SHORTCUTS`).
+    /// Skipped while a text field has focus so typing isn't hijacked.
+    fn handle_shortcuts(&mut self, ctx: &egui::Context) {
+        use egui::{Key, KeyboardShortcut, Modifiers};
+        if ctx.wants_keyboard_input() {
+            return;
+        }
+        let pressed = |mods: Modifiers, key: Key| {
+            ctx.input_mut(|i| i.consume_shortcut(&KeyboardShortcut::new(mods, key)))
+        };
+
+        // check the longer combos first; consume_shortcut ignores extra modifiers
+        if pressed(Modifiers::COMMAND | Modifiers::SHIFT, Key::Z) {
+            self.redo();
+        } else if pressed(Modifiers::COMMAND, Key::Z) {
+            self.undo();
+        }
+        if pressed(Modifiers::COMMAND, Key::S) {
+            self.save_scene();
+        }
+        if pressed(Modifiers::COMMAND, Key::D) {
+            self.duplicate_selected();
+        }
+        if pressed(Modifiers::NONE, Key::Delete) {
+            self.delete_selected();
+        }
+        if pressed(Modifiers::SHIFT, Key::F5) {
+            self.stop_run();
+        } else if pressed(Modifiers::NONE, Key::F5)
+            && self.project.is_some()
+            && self.run_child.is_none()
+        {
+            self.start_run();
+        }
+        // "F" frames the selection in the viewport
+        if pressed(Modifiers::NONE, Key::F) {
+            self.viewport.focus_requested = true;
+        }
+        // W/E/R pick the viewport gizmo
+        for (key, mode) in [
+            (Key::W, GizmoMode::Move),
+            (Key::E, GizmoMode::Rotate),
+            (Key::R, GizmoMode::Scale),
+            (Key::M, GizmoMode::Measure),
+        ] {
+            if pressed(Modifiers::NONE, key) {
+                self.viewport.gizmo_mode = mode;
+            }
+        }
+        if pressed(Modifiers::NONE, Key::Escape) {
+            self.viewport.ruler = None;
+        }
+
+        // Ctrl+C / Ctrl+V arrive as clipboard events rather than key presses
+        let (copy, paste) = ctx.input(|i| {
+            let copy = i.events.iter().any(|e| matches!(e, egui::Event::Copy));
+            let paste = i.events.iter().find_map(|e| match e {
+                egui::Event::Paste(text) => Some(text.clone()),
+                _ => None,
+            });
+            (copy, paste)
+        });
+        if copy {
+            self.copy_selected(ctx);
+        }
+        if let Some(text) = paste {
+            self.paste_entity(&text);
+        }
+    }
+
+    fn undo(&mut self) {
+        let Some(p) = &mut self.project else {
+            return;
+        };
+        self.last_log = if p.undo() { "undo" } else { "nothing to undo" }.into();
+        self.after_history_step();
+    }
+
+    fn redo(&mut self) {
+        let Some(p) = &mut self.project else {
+            return;
+        };
+        self.last_log = if p.redo() { "redo" } else { "nothing to redo" }.into();
+        self.after_history_step();
+    }
+
+    /// Drop per-entity UI state that may point past the restored scene.
+    fn after_history_step(&mut self) {
+        let count = self
+            .project
+            .as_ref()
+            .and_then(|p| p.design_scene.as_ref())
+            .map_or(0, |s| s.entities.len());
+        self.selected_entity = self.selected_entity.filter(|&s| s < count);
+        self.extra_selected.retain(|&s| s < count);
+        self.rename = None;
+        self.rename_error = None;
+        self.viewport.drag = None;
+    }
+
+    /// Put the selected entity on the clipboard as RON.
+    fn copy_selected(&mut self, ctx: &egui::Context) {
+        let Some(ent) = self
+            .selected_entity
+            .zip(self.project.as_ref())
+            .and_then(|(sel, p)| p.design_scene.as_ref()?.entities.get(sel))
+        else {
+            return;
+        };
+        match ron::ser::to_string_pretty(ent, ron::ser::PrettyConfig::default()) {
+            Ok(text) => {
+                ctx.copy_text(text);
+                self.last_log = format!("copied {}", ent.id);
+            }
+            Err(e) => self.last_log = format!("Copy failed: {e}"),
+        }
+    }
+
+    /// Append an entity parsed from clipboard `text` to the open scene, under
+    /// a fresh id if its own is taken, and select it.
+    fn paste_entity(&mut self, text: &str) {
+        let mut ent: crate::project::EntityDoc = match ron::from_str(text) {
+            Ok(ent) => ent,
+            Err(e) => {
+                self.last_log = format!("Clipboard doesn't hold an entity: {e}");
+                return;
+            }
+        };
+        let Some((idx, id)) = self.with_edit(|scene| {
+            ent.id = scene.unique_id(&ent.id);
+            // the parent may live in another scene
+            if ent
+                .parent
+                .as_deref()
+                .is_some_and(|id| scene.index_of(id).is_none())
+            {
+                ent.parent = None;
+            }
+            let id = ent.id.clone();
+            scene.entities.push(ent);
+            (scene.entities.len() - 1, id)
+        }) else {
+            return;
+        };
+        self.last_log = format!("pasted {id}");
+        self.selected_entity = Some(idx);
+        self.extra_selected.clear();
+    }
+
+    /// Save the selected entity as prefab `self.prefab_name`.
+    fn save_selected_as_prefab(&mut self) {
+        let (Some(p), Some(sel)) = (&self.project, self.selected_entity) else {
+            return;
+        };
+        let name = self.prefab_name.trim();
+        match p.save_prefab(name, sel) {
+            Ok(path) => {
+                self.last_log = format!("saved prefab {}", path.display());
+                self.prefabs = p.list_prefabs();
+            }
+            Err(e) => self.last_log = format!("Couldn't save prefab: {e:#}"),
+        }
+    }
+
+    /// Add an entity from prefab `name` and select it.
+    fn instantiate_prefab(&mut self, name: &str) {
+        let Some(p) = &mut self.project else {
+            return;
+        };
+        match p.instantiate_prefab(name) {
+            Ok(idx) => {
+                self.selected_entity = Some(idx);
+                self.extra_selected.clear();
+                self.last_log = format!("added {name}");
+            }
+            Err(e) => self.last_log = format!("Couldn't instantiate {name}: {e:#}"),
+        }
+    }
+
+    /// Delete entity `idx`; its children are reparented to its parent.
+    fn delete_entity(&mut self, idx: usize) {
+        if let Some(ent) = self
+            .project
+            .as_ref()
+            .and_then(|p| p.design_scene.as_ref()?.entities.get(idx))
+            && ent.is_locked()
+        {
+            self.last_log = format!("{} is locked; unlock it first", ent.id);
+            return;
+        }
+        let Some(removed) = self.with_edit(|scene| scene.remove_entity(idx)).flatten() else {
+            return;
+        };
+        self.last_log = format!("deleted {}", removed.id);
+        // indices after idx shifted down by one
+        let shift = |s: usize| match s.cmp(&idx) {
+            std::cmp::Ordering::Less => Some(s),
+            std::cmp::Ordering::Equal => None,
+            std::cmp::Ordering::Greater => Some(s - 1),
+        };
+        self.selected_entity = self.selected_entity.and_then(shift);
+        self.extra_selected = self
+            .extra_selected
+            .iter()
+            .filter_map(|&s| shift(s))
+            .collect();
+        self.rename = None;
+        self.rename_error = None;
+        self.viewport.drag = None;
+    }
+
+    /// Move entity `from` to just before its sibling `onto`, or just after it
+    /// when `below` (see `SceneDoc::move_beside`). The selection follows the
+    /// moved entities.
+    fn reorder_entity(&mut self, from: usize, onto: usize, below: bool) {
+        let Some(Some(to)) = self.with_edit(|scene| scene.move_beside(from, onto, below)) else {
+            return;
+        };
+        if from == to {
+            return;
+        }
+        let moved = |i: usize| match i {
+            i if i == from => to,
+            i if from < i && i <= to => i - 1,
+            i if to <= i && i < from => i + 1,
+            i => i,
+        };
+        self.selected_entity = self.selected_entity.map(moved);
+        self.extra_selected = self.extra_selected.iter().map(|&i| moved(i)).collect();
+        self.rename = None;
+        self.viewport.drag = None;
+    }
+
+    fn toggle_locked(&mut self, idx: usize) {
+        self.with_edit(|scene| {
+            if let Some(ent) = scene.entities.get_mut(idx) {
+                ent.locked = (!ent.is_locked()).then_some(true);
+            }
+        });
+    }
+
+    fn toggle_hidden(&mut self, idx: usize) {
+        self.with_edit(|scene| {
+            if let Some(ent) = scene.entities.get_mut(idx) {
+                ent.hidden = !ent.hidden;
+            }
+        });
+    }
+
+    // ---------- unsaved-changes helpers ----------
+
+    fn is_dirty(&self) -> bool {
+        self.project.as_ref().is_some_and(|p| p.dirty)
+    }
+
+    /// Save the design scene, reporting the outcome in `last_log`.
+    fn save_scene(&mut self) -> bool {
+        let Some(p) = &mut self.project else {
+            return false;
+        };
+        if p.design_path().is_none() {
+            return self.save_scene_as();
+        }
+        let ok = match p.save_design() {
+            Ok(_) => {
+                self.last_log = "scene saved".into();
+                true
+            }
+            Err(e) => {
+                self.last_log = format!("save failed: {e:#}");
+                false
+            }
+        };
+        self.egui_ctx.request_repaint();
+        ok
+    }
+
+    /// Render the in-memory scene to its thumbnail file on a worker thread;
+    /// `poll_thumbnail` picks up the result.
+    fn start_thumbnail(&mut self) {
+        let Some(p) = &self.project else {
+            return;
+        };
+        let (Some(scene), Some(out)) = (p.scene_for_preview(), p.thumbnail_path()) else {
+            return;
+        };
+        let (tx, rx) = unbounded();
+        let ctx = self.egui_ctx.clone();
+        std::thread::spawn(move || {
+            let res = crate::preview::render_thumbnail(&scene, &out).map(|()| out);
+            let _ = tx.send(res);
+            ctx.request_repaint();
+        });
+        self.thumbnail_rx = Some(rx);
+        self.last_log = "rendering thumbnail…".into();
+    }
+
+    fn poll_thumbnail(&mut self) {
+        let Some(rx) = &self.thumbnail_rx else {
+            return;
+        };
+        let res = match rx.try_recv() {
+            Ok(res) => res,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => Err(anyhow::anyhow!("the renderer crashed")),
+        };
+        self.thumbnail_rx = None;
+        self.last_log = match res {
+            Ok(path) => format!("thumbnail written to {}", path.display()),
+            Err(e) => format!("thumbnail failed: {e:#}"),
+        };
+    }
+
+    /// Load a JSON scene into memory; Save still writes the RON design file.
+    fn import_scene_json(&mut self) {
+        let Some(p) = &mut self.project else {
+            return;
+        };
+        let Some(path) = rfd::FileDialog::new()
+            .set_directory(&p.root)
+            .add_filter("Scene (JSON)", &["json"])
+            .pick_file()
+        else {
+            return;
+        };
+        self.last_log = match project::load_scene_json(&path) {
+            Ok(scene) => {
+                p.import_scene(scene);
+                self.selected_entity = None;
+                self.extra_selected.clear();
+                format!("imported {} (not saved yet)", path.display())
+            }
+            Err(e) => format!("import failed: {e:#}"),
+        };
+    }
+
+    fn export_scene_json(&mut self) {
+        let Some(scene) = self.project.as_ref().and_then(|p| p.design_scene.as_ref()) else {
+            return;
+        };
+        let Some(path) = rfd::FileDialog::new()
+            .set_file_name("scene.json")
+            .add_filter("Scene (JSON)", &["json"])
+            .save_file()
+        else {
+            return;
+        };
+        self.last_log = match project::save_scene_json(scene, &path) {
+            Ok(()) => format!("exported {}", path.display()),
+            Err(e) => format!("export failed: {e:#}"),
+        };
+    }
+
+    /// Ask for a scene path and save there; later saves reuse it.
+    fn save_scene_as(&mut self) -> bool {
+        let Some(p) = &mut self.project else {
+            return false;
+        };
+        let dir = p
+            .design_path()
+            .and_then(|d| d.parent())
+            .map(|d| d.to_path_buf())
+            .unwrap_or_else(|| p.root.join("design"));
+        let Some(path) = rfd::FileDialog::new()
+            .set_directory(&dir)
+            .set_file_name("initial.scene.ron")
+            .add_filter("Scene (RON)", &["ron"])
+            .save_file()
+        else {
+            return false; // cancelled
+        };
+        let ok = match p.save_design_as(path.clone()) {
+            Ok(_) => {
+                self.last_log = format!("scene saved to {}", path.display());
+                true
+            }
+            Err(e) => {
+                self.last_log = format!("save failed: {e:#}");
+                false
+            }
+        };
+        self.egui_ctx.request_repaint();
+        ok
+    }
+
+    fn sync_window_title(&mut self, ctx: &egui::Context) {
+        let title = match &self.project {
+            Some(p) => format!(
+                "Bevy Editor — {}{}",
+                p.config.name,
+                if p.dirty { " *" } else { "" }
+            ),
+            None => "Bevy Editor".into(),
+        };
+        if title != self.window_title {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Title(title.clone()));
+            self.window_title = title;
+        }
+    }
+
+    /// Write the recovery file every `autosave_secs` while the scene is dirty.
+    fn autosave_tick(&mut self, ctx: &egui::Context) {
+        if !self.autosave_enabled || !self.is_dirty() {
+            self.last_autosave = std::time::Instant::now();
+            return;
+        }
+        let interval = std::time::Duration::from_secs(self.autosave_secs.max(1));
+        let elapsed = self.last_autosave.elapsed();
+        if elapsed < interval {
+            ctx.request_repaint_after(interval - elapsed);
+            return;
+        }
+        self.last_autosave = std::time::Instant::now();
+        if let Some(p) = &self.project
+            && let Err(e) = p.write_autosave()
+        {
+            self.last_log = format!("autosave failed: {e:#}");
+        }
+    }
+
+    /// Ask whether to restore the scene from a newer autosave file.
+    fn recovery_modal(&mut self, ctx: &egui::Context) {
+        if !self.recovery_prompt {
+            return;
+        }
+        let mut recover = None;
+        let modal = egui::Modal::new(egui::Id::new("recovery_prompt")).show(ctx, |ui| {
+            ui.heading("Recover autosave?");
+            ui.label("An autosave newer than the scene file was found. Restore it?");
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button("Recover").clicked() {
+                    recover = Some(true);
+                }
+                if ui.button("Discard").clicked() {
+                    recover = Some(false);
+                }
+            });
+        });
+        if modal.should_close() && recover.is_none() {
+            // Esc / click outside: decide later, keep the file
+            self.recovery_prompt = false;
+            return;
+        }
+        let Some(recover) = recover else {
+            return;
+        };
+        self.recovery_prompt = false;
+        let Some(p) = &mut self.project else {
+            return;
+        };
+        if recover {
+            self.last_log = match p.recover_autosave() {
+                Ok(()) => "recovered scene from autosave (not saved yet)".into(),
+                Err(e) => format!("recovery failed: {e:#}"),
+            };
+        } else {
+            p.discard_autosave();
+        }
+    }
+
+    /// Hold back a window close while the scene is dirty and ask what to do.
+    fn guard_close(&mut self, ctx: &egui::Context) {
+        if ctx.input(|i| i.viewport().close_requested()) && self.is_dirty() && !self.allow_close {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            self.close_prompt = true;
+        }
+        if !self.close_prompt {
+            return;
+        }
+
+        let choice = unsaved_changes_modal(
+            ctx,
+            "close_prompt",
+            "The scene has unsaved changes. Save before closing?",
+        );
+        let close = match choice {
+            Some(UnsavedChoice::Save) => self.save_scene(),
+            Some(UnsavedChoice::Discard) => true,
+            Some(UnsavedChoice::Cancel) => false,
+            None => return,
+        };
+        self.close_prompt = false;
+        if close {
+            self.allow_close = true;
+            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+        }
+    }
+
+    /// Tear down the file watcher and its relay thread, waiting for both, so
+    /// a closed project can't queue more checks.
+    fn stop_watching(&mut self) {
+        if let Some(mut w) = self.watcher.take() {
+            w.stop();
+        }
+        if let Some(relay) = self.watch_relay.take() {
+            let _ = relay.join();
+        }
+    }
+
+    /// Ask what to do when the scene file changed on disk under unsaved edits:
+    /// overwrite it with ours, load it and drop ours, or keep editing ours.
+    fn guard_disk_conflict(&mut self, ctx: &egui::Context) {
+        if self
+            .project
+            .as_ref()
+            .is_none_or(|p| p.disk_conflict.is_none())
+        {
+            return;
+        }
+        let choice = unsaved_changes_modal(
+            ctx,
+            "disk_conflict_prompt",
+            "The scene file changed on disk, and you have unsaved changes. \
+             Save yours over it, discard yours and load it, or cancel to keep editing yours?",
+        );
+        let Some(choice) = choice else {
+            return;
+        };
+        let Some(p) = &mut self.project else {
+            return;
+        };
+        let disk = p.disk_conflict.take();
+        match (choice, disk) {
+            (UnsavedChoice::Save, _) => {
+                self.save_scene();
+            }
+            (UnsavedChoice::Discard, Some(scene)) => {
+                let ids = selection_ids(
+                    p.design_scene.as_ref(),
+                    self.selected_entity,
+                    &self.extra_selected,
+                );
+                p.adopt_disk_scene(scene);
+                self.last_log = "Loaded the scene from disk; undo brings your edits back".into();
+                self.reselect_after_reload(ids);
+            }
+            _ => {}
+        }
+    }
+
+    /// Find the selection again by id after the scene was replaced; indices
+    /// may point elsewhere in the new scene.
+    fn reselect_after_reload(&mut self, ids: (Option<String>, Vec<String>)) {
+        let scene = self.project.as_ref().and_then(|p| p.design_scene.as_ref());
+        (self.selected_entity, self.extra_selected) = reselect_ids(scene, ids);
+        self.rename = None;
+        self.viewport.drag = None;
+    }
+
+    /// Hold back "Open Project…" while the scene is dirty and ask what to do.
+    fn guard_open(&mut self, ctx: &egui::Context) {
+        if self.pending_open.is_none() {
+            return;
+        }
+        let choice = unsaved_changes_modal(
+            ctx,
+            "open_prompt",
+            "The scene has unsaved changes. Save before opening another project?",
+        );
+        let proceed = match choice {
+            Some(UnsavedChoice::Save) => self.save_scene(),
+            Some(UnsavedChoice::Discard) => true,
+            Some(UnsavedChoice::Cancel) => false,
+            None => return,
+        };
+        if let Some(path) = self.pending_open.take()
+            && proceed
+        {
+            self.open_project(path);
+        }
+    }
+
+    // ---------- runner helpers ----------
+
+    /// Why check/build/run can't be used right now, for disabled buttons.
+    fn cargo_unavailable(&self) -> Option<&'static str> {
+        match &self.project {
+            None => Some("Open a project first"),
+            Some(p) if !p.has_manifest() => Some("No Cargo.toml in the project root"),
+            Some(_) => None,
+        }
+    }
+
+    /// Run the project; with "check before run" on, the launch waits for a
+    /// clean `cargo check` (see `finish_run_gate`).
+    fn start_run(&mut self) {
+        if let Some(reason) = self.cargo_unavailable() {
+            self.last_log = reason.into();
+            return;
+        }
+        if !self.check_before_run {
+            self.launch_run();
+            return;
+        }
+        let Some(p) = &self.project else {
+            self.last_log = "no project open".into();
+            self.egui_ctx.request_repaint();
+            return;
+        };
+        if self.run_child.is_some() || self.run_gate.is_some() {
+            self.last_log = "runner already active".into();
+            self.egui_ctx.request_repaint();
+            return;
+        }
+        let _ = self.build_tx.send(BuildJob::Check {
+            root: p.root.clone(),
+            release: self.release_build.load(Ordering::Relaxed),
+        });
+        self.run_gate = Some(RunGate::Queued);
+        self.run_log.clear();
+        self.run_log.push("[run] checking before run".into());
+        self.last_log = "checking before run…".into();
+        self.egui_ctx.request_repaint();
+    }
+
+    /// A build finished; if a run is waiting on it, launch when it `ok`ed
+    /// (cargo exited cleanly with no errors) or give up.
+    fn finish_run_gate(&mut self, ok: bool, diagnostics: &[Diagnostic]) {
+        // results that were already queued before our check started don't count
+        if self.run_gate != Some(RunGate::Checking) {
+            return;
+        }
+        self.run_gate = None;
+        if ok {
+            self.launch_run();
+            return;
+        }
+        let errors: Vec<&Diagnostic> = diagnostics
+            .iter()
+            .filter(|d| !d.msg.starts_with("[warning]"))
+            .collect();
+        for d in &errors {
+            self.run_log.push(format!(
+                "[err] {}:{}:{} {}",
+                d.file.display(),
+                d.line,
+                d.col,
+                d.msg
+            ));
+        }
+        self.run_log.push(format!(
+            "[run] not started: check found {} error(s); use \"Run anyway\" to run regardless",
+            errors.len()
+        ));
+        self.last_log = "run aborted: check failed".into();
+    }
+
+    /// Spawn `cargo run` right away.
+    fn launch_run(&mut self) {
+        self.run_gate = None;
+        if let Some(reason) = self.cargo_unavailable() {
+            self.last_log = reason.into();
+            return;
+        }
+        let Some(p) = &self.project else {
+            self.last_log = "no project open".into();
+            self.egui_ctx.request_repaint();
+            return;
+        };
+        if self.run_child.is_some() {
+            self.last_log = "runner already active".into();
+            self.egui_ctx.request_repaint();
+            return;
+        }
+
+        let release = self.release_build.load(Ordering::Relaxed);
+        let options = self.run_options.get(&p.root).cloned().unwrap_or_default();
+        let game_args: Vec<String> = options
+            .args
+            .split_whitespace()
+            .map(str::to_string)
+            .collect();
+        let args = runner::RunArgs {
+            release,
+            bin: self.run_bin.as_deref(),
+            features: &self.run_features,
+            game_args: &game_args,
+        };
+        // rows with an empty key are unfinished edits
+        let env: Vec<(&str, &str)> = options
+            .env
+            .iter()
+            .filter(|(k, _)| !k.trim().is_empty())
+            .map(|(k, v)| (k.trim(), v.as_str()))
+            .collect();
+        let egui_ctx = self.egui_ctx.clone();
+        match runner::start(&p.root, &args, &env, move || egui_ctx.request_repaint()) {
+            Ok((child, rx)) => {
+                self.run_child = Some(child);
+                self.run_rx = Some(rx);
+                self.run_log.clear();
+                self.last_log = format!(
+                    "runner started ({})",
+                    if release { "release" } else { "debug" }
+                );
+                self.egui_ctx.request_repaint();
+            }
+            Err(e) => {
+                self.last_log = build::spawn_error_message(&e);
+                self.run_log.push(format!("[runner] {}", self.last_log));
+                self.egui_ctx.request_repaint();
+            }
+        }
+    }
+
+    fn stop_run(&mut self) {
+        if self.run_gate.take().is_some() {
+            self.run_log.push("[run] cancelled".into());
+            self.last_log = "run cancelled".into();
+        }
+        if let Some(child) = self.run_child.take() {
+            child.kill();
+            self.last_log = "runner stopped".into();
+        }
+        self.run_rx = None;
+        self.egui_ctx.request_repaint();
+    }
+
+    /// `cargo add bevy@<version>` in the project root, output to the console.
+    fn start_cargo_add(&mut self, version: &str) {
+        if let Some(reason) = self.cargo_unavailable() {
+            self.last_log = reason.into();
+            return;
+        }
+        let Some(p) = &self.project else {
+            return;
+        };
+        let spec = format!("bevy@{version}");
+        let ctx = self.egui_ctx.clone();
+        match runner::cargo_add(&p.root, &spec, move || ctx.request_repaint()) {
+            Ok((_handle, rx)) => {
+                self.run_log.push(format!("[cargo add] {spec}…"));
+                self.last_log = format!("cargo add {spec}…");
+                self.cargo_add_rx = Some(rx);
+            }
+            Err(e) => {
+                self.last_log = build::spawn_error_message(&e);
+                self.run_log.push(format!("[cargo add] {}", self.last_log));
+            }
+        }
+    }
+
+    fn pump_cargo_add(&mut self) {
+        let Some(rx) = &self.cargo_add_rx else {
+            return;
+        };
+        loop {
+            match rx.try_recv() {
+                Ok(RunnerMsg::Line(line)) => self.run_log.push(format!("[cargo add] {line}")),
+                Ok(RunnerMsg::Exited(code)) => {
+                    self.last_log = if code == 0 {
+                        "cargo add finished".into()
+                    } else {
+                        format!("cargo add failed (code {code})")
+                    };
+                    self.run_log
+                        .push(format!("[cargo add] exited (code {code})"));
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.cargo_add_rx = None;
+                    break;
+                }
+            }
+        }
+    }
+
+    fn project_settings_window(&mut self, ctx: &egui::Context) {
+        let Some(cfg) = &mut self.project_settings else {
+            return;
+        };
+        let mut open = true;
+        let mut save = false;
+        let mut add_bevy = false;
+        egui::Window::new("Project Settings")
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                let version_ok = project::valid_bevy_version(cfg.bevy_version.trim());
+                egui::Grid::new("project_settings_grid")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("Name");
+                        ui.text_edit_singleline(&mut cfg.name);
+                        ui.end_row();
+
+                        ui.label("Entry");
+                        ui.text_edit_singleline(&mut cfg.entry)
+                            .on_hover_text("Relative to the project root, e.g. src/main.rs");
+                        ui.end_row();
+
+                        ui.label("Bevy version");
+                        ui.horizontal(|ui| {
+                            ui.text_edit_singleline(&mut cfg.bevy_version);
+                            if !version_ok {
+                                ui.colored_label(ui.visuals().warn_fg_color, "⚠")
+                                    .on_hover_text("Expected e.g. 0.16 or 0.16.1");
+                            }
+                        });
+                        ui.end_row();
+
+                        ui.label("Grid spacing");
+                        ui.horizontal(|ui| {
+                            for step in GRID_SPACINGS {
+                                ui.selectable_value(&mut cfg.grid_spacing, step, format!("{step}"));
+                            }
+                        })
+                        .response
+                        .on_hover_text(
+                            "World units; the grid follows the snap step while snapping",
+                        );
+                        ui.end_row();
+
+                        let mut bounded = cfg.world_bounds.is_some();
+                        ui.checkbox(&mut bounded, "World bounds")
+                            .on_hover_text("Warn about entities placed outside this x/z area");
+                        if bounded != cfg.world_bounds.is_some() {
+                            cfg.world_bounds = bounded.then(WorldBounds::default);
+                        }
+                        if let Some(b) = &mut cfg.world_bounds {
+                            ui.horizontal(|ui| {
+                                ui.label("x");
+                                coord_drag(ui, &mut b.min.0, "min");
+                                coord_drag(ui, &mut b.max.0, "max");
+                                ui.label("z");
+                                coord_drag(ui, &mut b.min.1, "min");
+                                coord_drag(ui, &mut b.max.1, "max");
+                            });
+                            // keep min <= max whichever end was dragged past the other
+                            if b.min.0 > b.max.0 {
+                                std::mem::swap(&mut b.min.0, &mut b.max.0);
+                            }
+                            if b.min.1 > b.max.1 {
+                                std::mem::swap(&mut b.min.1, &mut b.max.1);
+                            }
+                        }
+                        ui.end_row();
+                    });
+                if let Some(err) = &self.project_settings_error {
+                    ui.colored_label(ui.visuals().error_fg_color, err);
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    save = ui.button("Save").clicked();
+                    let busy = self.cargo_add_rx.is_some();
+                    add_bevy = ui
+                        .add_enabled(
+                            version_ok && !busy,
+                            egui::Button::new(format!(
+                                "cargo add bevy@{}",
+                                cfg.bevy_version.trim()
+                            )),
+                        )
+                        .on_hover_text("Set the bevy dependency in Cargo.toml to this version")
+                        .on_disabled_hover_text(if busy {
+                            "cargo add is already running"
+                        } else {
+                            "Enter a version like 0.16 or 0.16.1"
+                        })
+                        .clicked();
+                });
+            });
+
+        let version = cfg.bevy_version.trim().to_string();
+        if save {
+            let mut cfg = cfg.clone();
+            cfg.bevy_version = version.clone();
+            if let Some(p) = &mut self.project {
+                match p.save_config(cfg) {
+                    Ok(()) => {
+                        self.last_log = "Saved project.ron".into();
+                        self.project_settings = None;
+                    }
+                    Err(e) => self.project_settings_error = Some(format!("{e:#}")),
+                }
             }
         }
+        if add_bevy {
+            self.start_cargo_add(&version);
+        }
+        if !open {
+            self.project_settings = None;
+        }
+    }
+
+    fn pump_run_log(&mut self) {
+        let Some(rx) = &self.run_rx else {
+            return;
+        };
+        // keep the receiver until every sender is gone, so output that
+        // trails the exit message still lands in the log
+        let mut disconnected = false;
+        loop {
+            match rx.try_recv() {
+                Ok(RunnerMsg::Line(line)) => self.run_log.push(line),
+                Ok(RunnerMsg::Exited(code)) => {
+                    self.run_child = None;
+                    self.last_log = format!("runner exited (code {code})");
+                    self.run_log.push(format!("[runner] exited (code {code})"));
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    disconnected = true;
+                    break;
+                }
+            }
+        }
+        if disconnected {
+            self.run_rx = None;
+        }
+        if self.run_log.len() > 5000 {
+            let drain = self.run_log.len() - 5000;
+            self.run_log.drain(0..drain);
+        }
     }
 }
 
 impl eframe::App for EditorApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        if let Some(p) = &mut self.project {
-            // Hot-reload scene file if changed
-            p.reload_design_if_changed();
+        self.poll_preview_exit();
 
-            // Hot-reload .schema.ron if changed
-            use std::fs;
-            let root = p.root.clone(); // take a copy while we have &mut p
-            if let Ok(mt) = fs::metadata(root.join("design/.schema.ron")).and_then(|m| m.modified())
+        // React to what the watcher saw: manifests refresh the run controls
+        // and config, design/ changes hot-reload the scene and .schema.ron
+        let changes: Vec<FsChange> = self
+            .fs_changes
+            .as_ref()
+            .map(|rx| rx.try_iter().collect())
+            .unwrap_or_default();
+        if changes
+            .iter()
+            .any(|c| matches!(c, FsChange::ManifestChanged(_)))
+        {
+            self.refresh_cargo_info();
+            if let Some(p) = &mut self.project
+                && let Err(e) = p.reload_config()
             {
-                let changed = match self.schema_mtime {
-                    None => true,
-                    Some(old) => mt > old,
-                };
-                if changed {
-                    self.load_script_schema_from(&root);
+                self.last_log = format!("project.ron: {e:#}");
+            }
+        }
+        let changed: Vec<&PathBuf> = changes
+            .iter()
+            .filter(|c| matches!(c, FsChange::SceneChanged(_)))
+            .flat_map(|c| c.paths())
+            .collect();
+        if !changed.is_empty()
+            && let Some(p) = &mut self.project
+        {
+            let touched = |target: Option<&std::path::Path>| {
+                target.is_some_and(|t| changed.iter().any(|c| *c == t))
+            };
+            let mut reloaded = None;
+            if touched(p.design_path()) {
+                let ids = selection_ids(
+                    p.design_scene.as_ref(),
+                    self.selected_entity,
+                    &self.extra_selected,
+                );
+                // still skips our own saves by mtime
+                if p.reload_design_if_changed() {
+                    reloaded = Some(ids);
                 }
             }
+            let root = p.root.clone(); // take a copy while we have &mut p
+            let prefabs_dir = p.prefabs_dir();
+            if changed.iter().any(|c| c.starts_with(&prefabs_dir)) {
+                self.prefabs = p.list_prefabs();
+            }
+            if let Some(ids) = reloaded {
+                self.reselect_after_reload(ids);
+            }
+            if touched(Some(&root.join("design/.schema.ron"))) {
+                self.load_script_schema_from(&root);
+            }
         }
 
         // drain build results
         while let Ok(msg) = self.build_rx.try_recv() {
             match msg {
-                BuildResult::Ok { duration_ms } => {
-                    self.last_log = format!("cargo check: OK in {duration_ms} ms");
+                BuildResult::Started { root, command } => {
+                    // a project switch can leave a job for the old root in flight
+                    let current = self.project.as_ref().is_some_and(|p| p.root == root);
+                    if current && self.run_gate.is_some() {
+                        self.run_gate = Some(RunGate::Checking);
+                        self.run_log.push(format!("[run] {command}…"));
+                    }
+                    self.build_running = current.then_some(command);
+                }
+                BuildResult::Ok {
+                    command,
+                    duration_ms,
+                    diagnostics,
+                } => {
+                    self.build_running = None;
+                    self.last_log = format!("{command}: OK in {duration_ms} ms");
+                    self.record_build(command, duration_ms, true, &diagnostics);
+                    self.finish_run_gate(true, &diagnostics);
+                    if let Some(p) = &mut self.project {
+                        p.last_diagnostics = diagnostics;
+                    }
                 }
                 BuildResult::Err {
+                    command,
                     duration_ms,
                     diagnostics,
                 } => {
-                    self.last_log = format!("cargo check: ERR in {duration_ms} ms");
+                    self.build_running = None;
+                    self.last_log = match &diagnostics[..] {
+                        // cargo didn't start at all (see build::spawn_error_message)
+                        [only] if only.line == 0 && duration_ms == 0 => only.msg.clone(),
+                        _ => format!("{command}: ERR in {duration_ms} ms"),
+                    };
+                    self.record_build(command, duration_ms, false, &diagnostics);
+                    self.finish_run_gate(false, &diagnostics);
                     if let Some(p) = &mut self.project {
                         p.last_diagnostics = diagnostics;
                     }
@@ -412,78 +2366,407 @@ impl eframe::App for EditorApp {
 
         // drain runner output
         self.pump_run_log();
+        self.pump_cargo_add();
+        self.poll_thumbnail();
+
+        self.handle_shortcuts(ctx);
 
         egui::TopBottomPanel::top("menubar").show(ctx, |ui| self.ui_menubar(ui));
+        // added before the console so it sits below it
+        egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| self.ui_status_bar(ui));
 
-        egui::SidePanel::left("hierarchy")
+        let hierarchy = egui::SidePanel::left("hierarchy")
             .resizable(true)
-            .default_width(240.0)
+            .default_width(self.layout.hierarchy_width)
             .show(ctx, |ui| {
                 ui.heading("Hierarchy");
 
+                let mut want_duplicate = false;
+                let mut toggle_hidden = None;
+                let mut toggle_locked = None;
+                let mut reorder = None;
+                let mut want_delete = false;
                 match &self.project {
                     Some(p) => {
+                        let script_errors = self
+                            .script_schema
+                            .as_ref()
+                            .map(|s| s.script_errors(&p.root, &p.last_diagnostics))
+                            .unwrap_or_default();
                         if let Some(scene) = &p.design_scene {
                             ui.label(format!("{} entities", scene.entities.len()));
                             ui.separator();
-                            for (i, ent) in scene.entities.iter().enumerate() {
+                            // (depth, rect) per row, to place the drop line after the loop
+                            let mut rows = Vec::new();
+                            let mut drop_at = None;
+                            for (i, depth) in scene.tree_order() {
+                                let ent = &scene.entities[i];
                                 let selected = self.selected_entity == Some(i);
-                                if ui.selectable_label(selected, &ent.id).clicked() {
-                                    self.selected_entity = Some(i);
+                                let highlighted = selected || self.extra_selected.contains(&i);
+                                let resp = ui
+                                    .horizontal(|ui| {
+                                        ui.add_space(depth as f32 * 14.0);
+                                        let (icon, tip) = if ent.hidden {
+                                            ("◌", "Show")
+                                        } else {
+                                            ("👁", "Hide")
+                                        };
+                                        if ui
+                                            .add(egui::Button::new(icon).small().frame(false))
+                                            .on_hover_text(tip)
+                                            .clicked()
+                                        {
+                                            toggle_hidden = Some(i);
+                                        }
+                                        let (icon, tip) = if ent.is_locked() {
+                                            ("🔒", "Unlock")
+                                        } else {
+                                            ("🔓", "Lock")
+                                        };
+                                        if ui
+                                            .add(egui::Button::new(icon).small().frame(false))
+                                            .on_hover_text(tip)
+                                            .clicked()
+                                        {
+                                            toggle_locked = Some(i);
+                                        }
+                                        // renders with Bevy's default material; hint at it
+                                        let bare_mesh = ent.component("Mesh3d").is_some()
+                                            && ent.component("Material3d").is_none();
+                                        let text = if scene.hidden_in_tree(i) {
+                                            egui::RichText::new(&ent.id).weak()
+                                        } else if bare_mesh {
+                                            egui::RichText::new(&ent.id)
+                                                .color(ui.visuals().warn_fg_color)
+                                        } else {
+                                            egui::RichText::new(&ent.id)
+                                        };
+                                        let label = ui
+                                            .selectable_label(highlighted, text)
+                                            .interact(egui::Sense::drag());
+                                        let label = if bare_mesh {
+                                            label.on_hover_text("Mesh3d without Material3d")
+                                        } else {
+                                            label
+                                        };
+                                        for (ty, badge) in COMPONENT_BADGES {
+                                            if ent.component(ty).is_some() {
+                                                ui.weak(badge).on_hover_text(ty);
+                                            }
+                                        }
+                                        let errors: Vec<String> = ent
+                                            .scripts
+                                            .iter()
+                                            .filter_map(|a| {
+                                                Some((a, script_errors.get(a.name.as_str())?))
+                                            })
+                                            .flat_map(|(a, diags)| {
+                                                diags.iter().map(move |d| {
+                                                    format!(
+                                                        "{}: {}:{} {}",
+                                                        a.name,
+                                                        d.file.display(),
+                                                        d.line,
+                                                        d.msg
+                                                    )
+                                                })
+                                            })
+                                            .collect();
+                                        if !errors.is_empty() {
+                                            ui.colored_label(ui.visuals().error_fg_color, "⛔")
+                                                .on_hover_text(errors.join("\n"));
+                                        }
+                                        label
+                                    });
+                                let (row, resp) = (resp.response, resp.inner);
+                                // drag a row onto a sibling to move it next to that one
+                                if resp.drag_started() {
+                                    egui::DragAndDrop::set_payload(ui.ctx(), i);
+                                }
+                                if resp.dragged() {
+                                    ui.ctx().set_cursor_icon(egui::CursorIcon::Grabbing);
+                                }
+                                let below = ui
+                                    .input(|inp| inp.pointer.interact_pos())
+                                    .is_some_and(|pos| pos.y > row.rect.center().y);
+                                let sibling = |from: &usize| {
+                                    *from != i && scene.parent_of(*from) == scene.parent_of(i)
+                                };
+                                if row.dnd_hover_payload::<usize>().is_some_and(|f| sibling(&f)) {
+                                    drop_at = Some((rows.len(), below));
                                 }
+                                if let Some(from) = row.dnd_release_payload::<usize>()
+                                    && sibling(&from)
+                                {
+                                    reorder = Some((*from, i, below));
+                                }
+                                rows.push((depth, row.rect));
+                                if resp.clicked() {
+                                    if ui.input(|inp| inp.modifiers.command) {
+                                        toggle_selection(
+                                            &mut self.selected_entity,
+                                            &mut self.extra_selected,
+                                            i,
+                                        );
+                                    } else {
+                                        self.selected_entity = Some(i);
+                                        self.extra_selected.clear();
+                                    }
+                                    self.rename_error = None;
+                                }
+                                let in_selection = selected || self.extra_selected.contains(&i);
+                                resp.context_menu(|ui| {
+                                    // acts on the whole selection if `i` is part of it
+                                    if !in_selection {
+                                        self.selected_entity = Some(i);
+                                        self.extra_selected.clear();
+                                    }
+                                    if ui.button("Duplicate  (Ctrl+D)").clicked() {
+                                        want_duplicate = true;
+                                        ui.close();
+                                    }
+                                    if ui.button("Delete  (Del)").clicked() {
+                                        want_delete = true;
+                                        ui.close();
+                                    }
+                                });
+                            }
+                            if let Some((at, below)) = drop_at {
+                                let (depth, rect) = rows[at];
+                                let y = if below {
+                                    // a move below lands after the row's children too
+                                    let last = rows[at + 1..]
+                                        .iter()
+                                        .take_while(|(d, _)| *d > depth)
+                                        .last()
+                                        .map_or(rect, |(_, r)| *r);
+                                    last.bottom()
+                                } else {
+                                    rect.top()
+                                };
+                                ui.painter().hline(
+                                    ui.max_rect().x_range(),
+                                    y,
+                                    ui.visuals().selection.stroke,
+                                );
                             }
                         } else {
-                            ui.label("No scene loaded yet.");
-                            ui.small("Put design/initial.scene.ron in the project.");
+                            if p.design_error.is_some() {
+                                ui.label("The scene file has errors (see the viewport).");
+                            } else {
+                                ui.label("No scene loaded yet.");
+                                ui.small(
+                                    "Add design/initial.scene.ron, or use File → Save Scene As….",
+                                );
+                            }
                         }
                     }
                     None => {
                         ui.label("Open a project.");
                     }
                 }
+                let mut want_save_prefab = false;
+                let mut instantiate = None;
+                if self.project.as_ref().is_some_and(|p| p.design_scene.is_some()) {
+                    ui.separator();
+                    ui.collapsing("Prefabs", |ui| {
+                        ui.horizontal(|ui| {
+                            ui.add(
+                                egui::TextEdit::singleline(&mut self.prefab_name)
+                                    .hint_text("name")
+                                    .desired_width(110.0),
+                            );
+                            let can_save = self.selected_entity.is_some()
+                                && project::valid_prefab_name(self.prefab_name.trim());
+                            want_save_prefab = ui
+                                .add_enabled(can_save, egui::Button::new("Save selected"))
+                                .on_hover_text("Save the selected entity to design/prefabs/")
+                                .on_disabled_hover_text(
+                                    "Select an entity and name the prefab (letters, digits, _ and -)",
+                                )
+                                .clicked();
+                        });
+                        if self.prefabs.is_empty() {
+                            ui.weak("No prefabs yet.");
+                        }
+                        for name in &self.prefabs {
+                            ui.horizontal(|ui| {
+                                if ui.small_button("Instantiate").clicked() {
+                                    instantiate = Some(name.clone());
+                                }
+                                ui.label(name);
+                            });
+                        }
+                    });
+                }
+                if want_save_prefab {
+                    self.save_selected_as_prefab();
+                }
+                if let Some(name) = instantiate {
+                    self.instantiate_prefab(&name);
+                }
+                if want_duplicate {
+                    self.duplicate_selected();
+                }
+                if let Some(i) = toggle_hidden {
+                    self.toggle_hidden(i);
+                }
+                if let Some(i) = toggle_locked {
+                    self.toggle_locked(i);
+                }
+                if let Some((from, onto, below)) = reorder {
+                    self.reorder_entity(from, onto, below);
+                }
+                if want_delete {
+                    self.delete_selected();
+                }
             });
+        self.layout.hierarchy_width = hierarchy.response.rect.width();
 
-        egui::SidePanel::right("inspector")
+        let inspector = egui::SidePanel::right("inspector")
             .resizable(true)
-            .default_width(360.0)
+            .default_width(self.layout.inspector_width)
             .show(ctx, |ui| {
                 ui.heading("Inspector");
+                let snap = self.viewport.snap();
 
+                let mut bulk = Vec::new();
                 if let Some(p) = &mut self.project {
                     
-                    if let (Some(scene), Some(sel)) = (&mut p.design_scene, self.selected_entity) {
+                    if !self.extra_selected.is_empty() && p.design_scene.is_some() {
+                        bulk.extend(draw_multi_selection(
+                            ui,
+                            self.extra_selected.len() + 1,
+                            &mut self.nudge,
+                            self.component_clipboard.as_ref(),
+                        ));
+                    } else if let (Some(scene), Some(sel)) = (&mut p.design_scene, self.selected_entity) {
                         let mut want_save = false;
+                        let mut want_reset_transform = false;
+                        let mut want_paste_component = false;
 
-                        {
-                            // ── begin short borrow of the selected entity
-                            let ent = scene
-                                .entities
-                                .get_mut(sel)
-                                .expect("selected index valid while drawing");
+                        let locked = scene.entities[sel].is_locked();
+                        if locked {
+                            ui.label("🔒 Locked. Unlock it in the hierarchy to edit.");
+                        }
+                        // field edits land in the scene directly; this is the
+                        // entity as it was before them, for the undo step
+                        let unedited = scene.entities[sel].clone();
+                        let mut edited = false;
+                        ui.add_enabled_ui(!locked, |ui| {
+                            if let Some((target, id)) =
+                                draw_entity_id(ui, scene, sel, &mut self.rename, self.rename_error.as_deref())
+                            {
+                                bulk.push(BulkOp::Rename(target, id));
+                            }
+                            if let Some(parent) = draw_parent(ui, scene, sel) {
+                                bulk.push(BulkOp::SetParent(sel, parent));
+                            }
 
-                            ui.monospace(format!("Entity: {}", ent.id));
-                            ui.separator();
+                            {
+                                // ── begin short borrow of the selected entity
+                                let ent = scene
+                                    .entities
+                                    .get_mut(sel)
+                                    .expect("selected index valid while drawing");
+                                let mut changed = false;
+
+                                ui.separator();
+
+                                let ent_id = ent.id.clone();
+                                let dups: Vec<bool> = (0..ent.components.len())
+                                    .map(|i| ent.is_duplicate_component(i))
+                                    .collect();
+                                let mut remove_comp: Option<usize> = None;
+                                for (ci, comp) in ent.components.iter_mut().enumerate() {
+                                    if dups[ci] {
+                                        ui.collapsing(format!("{} (duplicate)", comp.type_id), |ui| {
+                                            ui.colored_label(
+                                                ui.visuals().warn_fg_color,
+                                                "Ignored: an earlier component of this type wins.",
+                                            );
+                                            if ui.button("Remove").clicked() {
+                                                remove_comp = Some(ci);
+                                            }
+                                        });
+                                        continue;
+                                    }
+                                    // keyed by entity id, so open sections survive reloads and reorders
+                                    let section = egui::CollapsingHeader::new(&comp.type_id)
+                                        .id_salt((&ent_id, &comp.type_id))
+                                        .show(ui, |ui| match comp.type_id.as_str() {
+                                        "Transform"  => changed |= draw_transform(ui, &mut comp.data, snap, &mut self.vec3_clipboard, &mut want_reset_transform),
+                                        "Mesh3d"     => changed |= draw_mesh3d(ui, &mut comp.data, &p.root),
+                                        "Material3d" => changed |= draw_material3d(ui, &mut comp.data),
+                                        "PointLight" => changed |= draw_point_light(ui, &mut comp.data),
+                                        "Camera3d"   => changed |= draw_camera3d(ui, &mut comp.data),
+                                        _            => { ui.label("Unsupported component"); }
+                                    });
+                                    section.header_response.context_menu(|ui| {
+                                        if ui.button("Copy component").clicked() {
+                                            self.component_clipboard = Some(comp.clone());
+                                            ui.close();
+                                        }
+                                        want_paste_component |= paste_component_button(ui, self.component_clipboard.as_ref());
+                                    });
+                                }
+                                if let Some(ci) = remove_comp {
+                                    bulk.push(BulkOp::RemoveComponent(sel, ci));
+                                }
 
-                            for comp in &mut ent.components {
-                                ui.collapsing(&comp.type_id, |ui| match comp.type_id.as_str() {
-                                    "Transform"  => draw_transform(ui, &mut comp.data),
-                                    "Mesh3d"     => draw_mesh3d(ui, &mut comp.data),
-                                    "Material3d" => draw_material3d(ui, &mut comp.data),
-                                    "PointLight" => draw_point_light(ui, &mut comp.data),
-                                    "Camera3d"   => { ui.label("No editable fields"); }
-                                    _            => { ui.label("Unsupported component"); }
+                                // only offer types the entity doesn't have yet
+                                let missing: Vec<&str> = COMPONENT_TYPES
+                                    .into_iter()
+                                    .filter(|t| ent.component(t).is_none())
+                                    .collect();
+                                ui.add_enabled_ui(!missing.is_empty(), |ui| {
+                                    ui.menu_button("Add component", |ui| {
+                                        for t in missing {
+                                            if ui.button(t).clicked() {
+                                                bulk.push(BulkOp::AddComponent(sel, t));
+                                                ui.close();
+                                            }
+                                        }
+                                    });
                                 });
-                            }
+                                // pasting a type the entity lacks adds it
+                                if self.component_clipboard.is_some() {
+                                    want_paste_component |= paste_component_button(ui, self.component_clipboard.as_ref());
+                                }
 
-                            ui.separator();
-                            // just set a flag; do NOT call save while `ent` is borrowed
-                            if ui.button("Save scene").clicked() {
-                                want_save = true;
-                            }
+                                bulk.extend(Self::draw_scripts_section(ui, sel, ent, self.script_schema.as_ref()));
+
+                                edited |= changed;
+                            } // ── entity borrow ends here
+                        });
+
+                        ui.separator();
+                        if ui.button("Save scene").clicked() {
+                            want_save = true;
+                        }
+
+                        // applied below as one undo step each, once `p` is free
+                        if want_reset_transform {
+                            bulk.push(BulkOp::ResetTransform(sel));
+                        }
+                        if want_paste_component {
+                            bulk.push(BulkOp::PasteComponent);
+                        }
 
-                            // scripts UI also needs &mut ent, so keep it inside this scope
-                            Self::draw_scripts_section(ui, ent, self.script_schema.as_ref());
-                        } // ── entity borrow ends here
+                        // one undo step per drag or typed value: pushed on its
+                        // first edited frame, joined by the rest until released
+                        if edited {
+                            let ongoing = self.inspector_edit == Some((sel, p.undo.len()));
+                            if !ongoing {
+                                let mut before = scene.clone();
+                                before.entities[sel] = unedited;
+                                p.push_undo(before);
+                                self.inspector_edit = Some((sel, p.undo.len()));
+                            }
+                            p.mark_edited();
+                        } else if !ui.input(|i| i.pointer.any_down()) && ui.memory(|m| m.focused().is_none()) {
+                            self.inspector_edit = None;
+                        }
 
                         // Now it's safe to call methods that borrow `p` mutably.
                         if want_save {
@@ -496,9 +2779,14 @@ impl eframe::App for EditorApp {
                     }
 
                     ui.separator();
-                    if ui.button("Run cargo check").clicked() {
+                    if ui
+                        .add_enabled(p.has_manifest(), egui::Button::new("Run cargo check"))
+                        .on_disabled_hover_text("No Cargo.toml in the project root")
+                        .clicked()
+                    {
                         let _ = self.build_tx.send(BuildJob::Check {
                             root: p.root.clone(),
+                            release: self.release_build.load(Ordering::Relaxed),
                         });
                         self.egui_ctx.request_repaint();
                     }
@@ -507,15 +2795,107 @@ impl eframe::App for EditorApp {
                     ui.separator();
                     ui.collapsing("Diagnostics", |ui| {
                         for d in &p.last_diagnostics {
-                            ui.label(format!(
-                                "{}:{}:{} {}",
-                                d.file.display(),
-                                d.line,
-                                d.col,
-                                d.msg
-                            ));
+                            let resp = ui
+                                .selectable_label(
+                                    false,
+                                    format!(
+                                        "{}:{}:{} {}",
+                                        d.file.display(),
+                                        d.line,
+                                        d.col,
+                                        d.msg
+                                    ),
+                                )
+                                .on_hover_text(format!(
+                                    "{}:{} – {}:{}\nDouble-click to open in editor (${})",
+                                    d.line,
+                                    d.col,
+                                    d.line_end,
+                                    d.col_end,
+                                    external::EDITOR_ENV
+                                ));
+                            if resp.double_clicked() {
+                                self.last_log =
+                                    match external::open_in_editor(&p.root, &d.file, d.line, d.col)
+                                    {
+                                        Ok(()) => format!("opened {}:{}", d.file.display(), d.line),
+                                        Err(e) => format!("failed to open editor: {e}"),
+                                    };
+                            }
+                        }
+                    });
+
+                    ui.separator();
+                    // scripts the current schema no longer knows (renamed/removed in code)
+                    let orphans = match (&self.script_schema, &p.design_scene) {
+                        (Some(schema), Some(scene)) => {
+                            scene.orphaned_scripts(&schema.script_names())
+                        }
+                        _ => Vec::new(),
+                    };
+                    let problems = p.validation.len() + orphans.len();
+                    let validation_title = if problems == 0 {
+                        "Validation".to_string()
+                    } else {
+                        format!("Validation ({problems})")
+                    };
+                    let mut remove_orphans = false;
+                    ui.collapsing(validation_title, |ui| {
+                        if problems == 0 {
+                            ui.small("No problems found.");
+                        }
+                        for (entity, script) in &orphans {
+                            let text = egui::RichText::new(format!(
+                                "⚠ {entity}: references unknown script '{script}'"
+                            ))
+                            .color(ui.visuals().warn_fg_color);
+                            if ui
+                                .selectable_label(false, text)
+                                .on_hover_text("Click to select the entity")
+                                .clicked()
+                                && let Some(scene) = &p.design_scene
+                            {
+                                self.selected_entity = scene.index_of(entity);
+                                self.extra_selected.clear();
+                            }
+                        }
+                        if !orphans.is_empty() {
+                            remove_orphans = ui
+                                .button("Remove orphaned scripts")
+                                .on_hover_text("Detach every script missing from design/.schema.ron")
+                                .clicked();
+                        }
+                        for issue in &p.validation {
+                            let (icon, color) = match issue.severity {
+                                project::Severity::Error => ("⛔", ui.visuals().error_fg_color),
+                                project::Severity::Warning => ("⚠", ui.visuals().warn_fg_color),
+                            };
+                            let text = egui::RichText::new(format!(
+                                "{icon} {}: {}",
+                                issue.entity, issue.message
+                            ))
+                            .color(color);
+                            if ui
+                                .selectable_label(false, text)
+                                .on_hover_text("Click to select the entity")
+                                .clicked()
+                                && let Some(scene) = &p.design_scene
+                            {
+                                self.selected_entity = scene.index_of(&issue.entity);
+                                self.extra_selected.clear();
+                            }
                         }
                     });
+                    if remove_orphans
+                        && let Some(schema) = &self.script_schema
+                        && let Some(scene) = &mut p.design_scene
+                    {
+                        let before = scene.clone();
+                        let removed = scene.remove_orphaned_scripts(&schema.script_names());
+                        p.push_undo(before);
+                        p.mark_edited();
+                        self.last_log = format!("removed {removed} orphaned script(s)");
+                    }
 
                     ui.separator();
                     ui.collapsing("Scripts (schema)", |ui| {
@@ -532,17 +2912,63 @@ impl eframe::App for EditorApp {
                     });
 
                    // Stage the root we want to export from
-                    let mut want_export: Option<std::path::PathBuf> = None;
+                    let mut want_export: Option<(std::path::PathBuf, String, Vec<String>)> = None;
+
+                    ui.horizontal(|ui| {
+                        let Some(p) = self.project.as_mut() else {
+                            return;
+                        };
+                        ui.label("Exporter bin");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut p.config.export_bin)
+                                .desired_width(110.0),
+                        );
+                        ui.label("features");
+                        let mut features = p.config.export_features.join(",");
+                        if ui
+                            .add(egui::TextEdit::singleline(&mut features).desired_width(110.0))
+                            .on_hover_text("Comma-separated; set export_bin / export_features in project.ron to keep them")
+                            .changed()
+                        {
+                            p.config.export_features = features
+                                .split(',')
+                                .map(str::trim)
+                                .filter(|f| !f.is_empty())
+                                .map(String::from)
+                                .collect();
+                        }
+                    });
 
-                    if ui.button("Export meta").clicked() {
-                        if let Some(p) = &self.project {
-                            want_export = Some(p.root.clone());
+                    if ui.button("Export meta").clicked()
+                        && let Some(p) = &self.project
+                    {
+                        let bin = p.config.export_bin.trim();
+                        let known = p.cargo.as_ref().map(|c| c.bins());
+                        match known {
+                            Some(bins) if !bins.contains(&bin) => {
+                                self.last_log = if bins.is_empty() {
+                                    format!("No bin '{bin}': Cargo.toml declares no binaries.")
+                                } else {
+                                    format!(
+                                        "No bin '{bin}' in Cargo.toml (have: {}). Set export_bin in project.ron.",
+                                        bins.join(", ")
+                                    )
+                                };
+                            }
+                            _ => {
+                                want_export = Some((
+                                    p.root.clone(),
+                                    bin.to_string(),
+                                    p.config.export_features.clone(),
+                                ));
+                            }
                         }
                     }
 
                     // Run export after the borrow of `p` has ended
-                    if let Some(root) = want_export {
-                        match build_meta::export_schema(&root, &[]) {
+                    if let Some((root, bin, features)) = want_export {
+                        let features: Vec<&str> = features.iter().map(String::as_str).collect();
+                        match build_meta::export_schema(&root, &bin, &features, &[]) {
                             Ok(res) => {
                                 // show logs in your console
                                 if !res.stdout.is_empty() {
@@ -572,7 +2998,7 @@ impl eframe::App for EditorApp {
                                 }
                             }
                             Err(e) => {
-                                self.last_log = format!("Failed to run exporter: {e}");
+                                self.last_log = build::spawn_error_message(&e);
                             }
                         }
                         self.egui_ctx.request_repaint();
@@ -582,40 +3008,178 @@ impl eframe::App for EditorApp {
                 } else {
                     ui.label("Open a project to inspect.");
                 }
+                for op in bulk {
+                    match op {
+                        BulkOp::Nudge => self.nudge_selected(),
+                        BulkOp::Align(axis, to) => self.align_selected(axis, to),
+                        BulkOp::Distribute(axis) => self.distribute_selected(axis),
+                        BulkOp::Duplicate => self.duplicate_selected(),
+                        BulkOp::Delete => self.delete_selected(),
+                        BulkOp::ResetTransform(idx) => self.reset_transform_of(idx),
+                        BulkOp::PasteComponent => self.paste_component_on_selection(),
+                        BulkOp::Rename(idx, id) => self.rename_entity(idx, &id),
+                        BulkOp::SetParent(idx, parent) => {
+                            self.with_edit(|scene| scene.set_parent(idx, parent.as_deref()));
+                        }
+                        BulkOp::AddComponent(idx, type_id) => self.edit_entity(idx, |ent| {
+                            ent.components.push(ComponentDoc {
+                                type_id: type_id.to_string(),
+                                data: CompData::default(),
+                            })
+                        }),
+                        BulkOp::RemoveComponent(idx, ci) => self.edit_entity(idx, |ent| {
+                            if ci < ent.components.len() {
+                                ent.components.remove(ci);
+                            }
+                        }),
+                        BulkOp::AttachScript(idx, script) => {
+                            self.edit_entity(idx, |ent| ent.scripts.push(script))
+                        }
+                        BulkOp::DetachScript(idx, i) => self.edit_entity(idx, |ent| {
+                            if i < ent.scripts.len() {
+                                ent.scripts.remove(i);
+                            }
+                        }),
+                    }
+                }
             });
+        self.layout.inspector_width = inspector.response.rect.width();
 
         // --- Console / Logs bottom panel (ALWAYS VISIBLE) ---
-        egui::TopBottomPanel::bottom("console")
+        let console = egui::TopBottomPanel::bottom("console")
             .resizable(true)
-            .default_height(160.0)
+            .default_height(self.layout.console_height)
             .show(ctx, |ui| {
                 ui.horizontal(|ui| {
                     ui.heading("Console");
                     ui.separator();
                     if let Some(p) = &self.project {
-                        if ui.button("Run cargo check").clicked() {
-                            let _ = self.build_tx.send(BuildJob::Check {
-                                root: p.root.clone(),
-                            });
-                        }
+                        let release = self.release_build.load(Ordering::Relaxed);
+                        ui.add_enabled_ui(p.has_manifest(), |ui| {
+                            if ui
+                                .button("Run cargo check")
+                                .on_disabled_hover_text("No Cargo.toml in the project root")
+                                .clicked()
+                            {
+                                let _ = self.build_tx.send(BuildJob::Check {
+                                    root: p.root.clone(),
+                                    release,
+                                });
+                            }
+                            if ui
+                                .button("Clippy")
+                                .on_hover_text("Run cargo clippy; lints show under Diagnostics")
+                                .on_disabled_hover_text("No Cargo.toml in the project root")
+                                .clicked()
+                            {
+                                let _ = self.build_tx.send(BuildJob::Clippy {
+                                    root: p.root.clone(),
+                                    release,
+                                });
+                            }
+                            if ui
+                                .button("Build")
+                                .on_disabled_hover_text("No Cargo.toml in the project root")
+                                .clicked()
+                            {
+                                let _ = self.build_tx.send(BuildJob::Build {
+                                    root: p.root.clone(),
+                                    release,
+                                });
+                            }
+                        });
                     }
                     ui.separator();
+                    if let Some(command) = &self.build_running {
+                        ui.spinner(); // keeps repainting until the result lands
+                        ui.label(if command.starts_with("cargo build") {
+                            "building…"
+                        } else if command.starts_with("cargo clippy") {
+                            "linting…"
+                        } else {
+                            "checking…"
+                        })
+                        .on_hover_text(command);
+                        ui.separator();
+                    }
                     ui.label(&self.last_log);
                 });
+                ui.horizontal(|ui| {
+                    ui.label("Filter:");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.console_filter)
+                            .hint_text("substring")
+                            .desired_width(200.0),
+                    );
+                    if ui
+                        .add_enabled(!self.console_filter.is_empty(), egui::Button::new("×"))
+                        .clicked()
+                    {
+                        self.console_filter.clear();
+                    }
+                    ui.separator();
+                    if ui
+                        .add_enabled(!self.run_log.is_empty(), egui::Button::new("Copy"))
+                        .on_hover_text("Copy the shown lines to the clipboard")
+                        .clicked()
+                    {
+                        let text = self
+                            .run_log
+                            .iter()
+                            .map(|l| ansi::strip(l))
+                            .filter(|l| l.contains(self.console_filter.as_str()))
+                            .collect::<Vec<_>>()
+                            .join("\n");
+                        ui.ctx().copy_text(text);
+                    }
+                    if ui
+                        .add_enabled(!self.run_log.is_empty(), egui::Button::new("Clear"))
+                        .clicked()
+                    {
+                        self.run_log.clear();
+                    }
+                });
                 ui.separator();
-                egui::ScrollArea::vertical()
-                    .stick_to_bottom(true)
-                    .auto_shrink([false; 2])
-                    .show(ui, |ui| {
-                        if self.run_log.is_empty() {
-                            ui.label("Runner output will be shown here.");
-                        } else {
-                            for line in &self.run_log {
-                                ui.monospace(line);
+                // Only the visible rows are laid out; lines are truncated (full
+                // text on hover) so every row has the same height.
+                let shown: Vec<&String> = if self.console_filter.is_empty() {
+                    self.run_log.iter().collect()
+                } else {
+                    self.run_log
+                        .iter()
+                        .filter(|l| ansi::strip(l).contains(self.console_filter.as_str()))
+                        .collect()
+                };
+                if self.run_log.is_empty() {
+                    ui.label("Runner output will be shown here.");
+                } else {
+                    let font = egui::TextStyle::Monospace.resolve(ui.style());
+                    let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+                    let normal = ui.visuals().text_color();
+                    let strong = ui.visuals().strong_text_color();
+                    let warn = ui.visuals().warn_fg_color;
+                    egui::ScrollArea::vertical()
+                        .stick_to_bottom(true)
+                        .auto_shrink([false; 2])
+                        .show_rows(ui, row_height, shown.len(), |ui, rows| {
+                            for line in &shown[rows] {
+                                let is_err = line.starts_with("[err]")
+                                    || line.starts_with("[export/stderr]");
+                                let base = if is_err { warn } else { normal };
+                                ui.add(
+                                    egui::Label::new(ansi::layout_line(
+                                        line,
+                                        font.clone(),
+                                        base,
+                                        strong,
+                                    ))
+                                    .truncate(),
+                                );
                             }
-                        }
-                    });
+                        });
+                }
             });
+        self.layout.console_height = console.response.rect.height();
 
         // --- Main viewport (scene preview) ---
         egui::CentralPanel::default().show(ctx, |ui| {
@@ -624,32 +3188,186 @@ impl eframe::App for EditorApp {
 
             // Run controls
             ui.horizontal(|ui| {
-                let running = self.run_child.is_some();
+                let running = self.run_child.is_some() || self.run_gate.is_some();
+                let unavailable = self.cargo_unavailable();
+                let idle = !running && unavailable.is_none();
+                let why = unavailable.unwrap_or("Already running");
                 if ui
-                    .add_enabled(
-                        !running && self.project.is_some(),
-                        egui::Button::new("Run project"),
-                    )
+                    .add_enabled(idle, egui::Button::new("Run project"))
+                    .on_disabled_hover_text(why)
                     .clicked()
                 {
                     self.start_run();
                 }
+                if self.check_before_run
+                    && ui
+                        .add_enabled(idle, egui::Button::new("Run anyway"))
+                        .on_hover_text("Skip the check and run right away")
+                        .on_disabled_hover_text(why)
+                        .clicked()
+                {
+                    self.launch_run();
+                }
                 if ui.add_enabled(running, egui::Button::new("Stop")).clicked() {
                     self.stop_run();
                 }
+                ui.checkbox(&mut self.check_before_run, "Check first")
+                    .on_hover_text("Run only after a cargo check without errors");
+                let mut release = self.release_build.load(Ordering::Relaxed);
+                ComboBox::from_id_salt("build_profile")
+                    .selected_text(if release { "release" } else { "debug" })
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut release, false, "debug");
+                        ui.selectable_value(&mut release, true, "release");
+                    })
+                    .response
+                    .on_hover_text("Profile used for check, build and run");
+                self.release_build.store(release, Ordering::Relaxed);
+
+                if let Some(cargo) = self.project.as_ref().and_then(|p| p.cargo.as_ref()) {
+                    let bins = cargo.bins();
+                    if bins.len() > 1 {
+                        ComboBox::from_id_salt("run_bin")
+                            .selected_text(self.run_bin.as_deref().unwrap_or("(default bin)"))
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut self.run_bin, None, "(default bin)");
+                                for b in bins {
+                                    ui.selectable_value(&mut self.run_bin, Some(b.to_string()), b);
+                                }
+                            });
+                    }
+                    let features = cargo.features();
+                    if !features.is_empty() {
+                        let label = if self.run_features.is_empty() {
+                            "features".to_string()
+                        } else {
+                            format!("features ({})", self.run_features.len())
+                        };
+                        ui.menu_button(label, |ui| {
+                            for f in features {
+                                let mut on = self.run_features.contains(&f);
+                                if ui.checkbox(&mut on, &f).changed() {
+                                    if on {
+                                        self.run_features.push(f);
+                                    } else {
+                                        self.run_features.retain(|x| *x != f);
+                                    }
+                                }
+                            }
+                        });
+                    }
+                }
+                if let Some(p) = &self.project {
+                    let options = self.run_options.entry(p.root.clone()).or_default();
+                    run_options_menu(ui, options);
+                }
                 ui.label(if running {
                     "Status: running"
                 } else {
                     "Status: idle"
                 });
+                ui.separator();
+                let focus_label = if self.selected_entity.is_some() {
+                    "Focus selected"
+                } else {
+                    "Frame all"
+                };
+                if ui
+                    .add_enabled(self.project.is_some(), egui::Button::new(focus_label))
+                    .on_hover_text("F")
+                    .clicked()
+                {
+                    self.viewport.focus_requested = true;
+                }
+                if ui
+                    .add_enabled(
+                        self.project.is_some() && self.preview.is_none(),
+                        egui::Button::new("3D preview"),
+                    )
+                    .on_hover_text("Open a Bevy window that follows your edits")
+                    .clicked()
+                {
+                    self.ensure_preview();
+                }
+                let embedded_label = if self.embedded.is_some() {
+                    "Hide embedded 3D"
+                } else {
+                    "Embedded 3D"
+                };
+                if ui
+                    .add_enabled(self.project.is_some(), egui::Button::new(embedded_label))
+                    .on_hover_text("Render the preview inside the editor, above the 2D view")
+                    .clicked()
+                {
+                    self.toggle_embedded_preview();
+                }
+                ui.checkbox(&mut self.preview_top_down, "Top-down")
+                    .on_hover_text("Preview through an orthographic camera framed like this view");
+                ui.separator();
+                for (mode, label, key) in [
+                    (GizmoMode::Move, "Move", "W"),
+                    (GizmoMode::Rotate, "Rotate", "E"),
+                    (GizmoMode::Scale, "Scale", "R"),
+                    (GizmoMode::Measure, "Ruler", "M"),
+                ] {
+                    ui.selectable_value(&mut self.viewport.gizmo_mode, mode, label)
+                        .on_hover_text(key);
+                }
+                if self.viewport.ruler.is_some()
+                    && ui
+                        .small_button("✖")
+                        .on_hover_text("Clear the measurement (Esc)")
+                        .clicked()
+                {
+                    self.viewport.ruler = None;
+                }
+                ui.separator();
+                ui.checkbox(&mut self.viewport.snap_enabled, "Snap");
+                ui.add_enabled(
+                    self.viewport.snap_enabled,
+                    DragValue::new(&mut self.viewport.snap_step)
+                        .speed(0.05)
+                        .range(0.05..=10.0)
+                        .suffix(" u"),
+                );
             });
 
             ui.separator();
 
+            // A broken design file on disk; whatever is in memory stays editable
+            if let Some(err) = self.project.as_ref().and_then(|p| p.design_error.as_ref()) {
+                egui::Frame::group(ui.style()).show(ui, |ui| {
+                    ui.colored_label(
+                        ui.visuals().error_fg_color,
+                        "Couldn't parse the scene file:",
+                    );
+                    ui.monospace(err);
+                });
+            }
+
+            if let Some(e) = &mut self.embedded {
+                show_embedded_preview(ui, e);
+            }
+
             // Scene preview
-            if let Some(p) = &self.project {
-                if let Some(scene) = &p.design_scene {
-                    draw_scene_preview(ui, scene, &mut self.view_offset, &mut self.view_zoom);
+            if let Some(p) = &mut self.project {
+                if let Some(scene) = &mut p.design_scene {
+                    let edit = draw_scene_preview(
+                        ui,
+                        scene,
+                        &p.config,
+                        &mut self.selected_entity,
+                        &mut self.extra_selected,
+                        &mut self.viewport,
+                    );
+                    match edit {
+                        ViewportEdit::None => {}
+                        ViewportEdit::Live => p.scene_changed(),
+                        ViewportEdit::Finished(before) => {
+                            p.push_undo(before);
+                            p.mark_edited();
+                        }
+                    }
                 } else {
                     ui.label("No scene loaded yet (design/initial.scene.ron).");
                 }
@@ -657,214 +3375,1593 @@ impl eframe::App for EditorApp {
                 ui.label("No project open.");
             }
         });
+
+        egui::Window::new("Shortcuts")
+            .open(&mut self.show_shortcuts)
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("shortcuts_grid")
+                    .num_columns(2)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (keys, action) in SHORTCUTS {
+                            ui.monospace(*keys);
+                            ui.label(*action);
+                            ui.end_row();
+                        }
+                    });
+            });
+
+        egui::Window::new("Settings")
+            .open(&mut self.show_settings)
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                let mut settings = self.watch_settings.lock().unwrap();
+                egui::Grid::new("settings_grid")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("Watch debounce");
+                        ui.add(
+                            DragValue::new(&mut settings.debounce_ms)
+                                .range(0..=5000)
+                                .suffix(" ms"),
+                        )
+                        .on_hover_text("Quiet time after file changes before they're acted on");
+                        ui.end_row();
+
+                        ui.label("cargo check");
+                        ui.horizontal(|ui| {
+                            ui.radio_value(&mut settings.auto_check, true, "On save");
+                            ui.radio_value(&mut settings.auto_check, false, "Manually");
+                        });
+                        ui.end_row();
+
+                        ui.label("Lint with");
+                        ui.horizontal(|ui| {
+                            ui.radio_value(&mut settings.clippy, false, "cargo check");
+                            ui.radio_value(&mut settings.clippy, true, "cargo clippy");
+                        })
+                        .response
+                        .on_hover_text("Used for on-save runs and when a project opens");
+                        ui.end_row();
+                    });
+            });
+
+        self.project_settings_window(ctx);
+        self.sync_preview(ctx);
+        self.autosave_tick(ctx);
+        self.recovery_modal(ctx);
+        self.sync_window_title(ctx);
+        self.guard_disk_conflict(ctx);
+        self.guard_open(ctx);
+        self.guard_close(ctx);
+    }
+
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, THEME_KEY, &self.theme);
+        eframe::set_value(
+            storage,
+            WATCH_SETTINGS_KEY,
+            &*self.watch_settings.lock().unwrap(),
+        );
+        eframe::set_value(storage, CHECK_BEFORE_RUN_KEY, &self.check_before_run);
+        eframe::set_value(storage, LAYOUT_KEY, &self.layout);
+        eframe::set_value(storage, RUN_OPTIONS_KEY, &self.run_options);
     }
 
     fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
         // don't leave a rogue process chewing ammo
         self.stop_run();
+        self.stop_watching();
+    }
+}
+
+/// Run toolbar menu editing the game's arguments and environment.
+fn run_options_menu(ui: &mut egui::Ui, options: &mut RunOptions) {
+    let set = usize::from(!options.args.trim().is_empty()) + options.env.len();
+    let label = if set == 0 {
+        "args/env".to_string()
+    } else {
+        format!("args/env ({set})")
+    };
+    ui.menu_button(label, |ui| {
+        ui.horizontal(|ui| {
+            ui.label("Args");
+            ui.add(
+                egui::TextEdit::singleline(&mut options.args)
+                    .hint_text("--level forest")
+                    .desired_width(220.0),
+            )
+            .on_hover_text("Passed to the game after `--`, split on whitespace");
+        });
+        ui.separator();
+        ui.label("Environment");
+        let mut remove = None;
+        for (i, (key, value)) in options.env.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(key)
+                        .hint_text("WGPU_BACKEND")
+                        .desired_width(120.0),
+                );
+                ui.label("=");
+                ui.add(egui::TextEdit::singleline(value).desired_width(120.0));
+                if ui.small_button("✖").on_hover_text("Remove").clicked() {
+                    remove = Some(i);
+                }
+            });
+        }
+        if let Some(i) = remove {
+            options.env.remove(i);
+        }
+        if ui.button("+ Variable").clicked() {
+            options.env.push(Default::default());
+        }
+    });
+}
+
+/// The newest embedded frame, scaled to fit the panel width and at most half
+/// its height.
+fn show_embedded_preview(ui: &mut egui::Ui, e: &mut Embedded3d) {
+    if let Some(frame) = e.frames.try_iter().last() {
+        let image = egui::ColorImage::from_rgba_unmultiplied(frame.size, &frame.rgba);
+        match &mut e.texture {
+            Some(t) => t.set(image, egui::TextureOptions::LINEAR),
+            None => {
+                e.texture = Some(ui.ctx().load_texture(
+                    "embedded_preview",
+                    image,
+                    egui::TextureOptions::LINEAR,
+                ))
+            }
+        }
+    }
+    let (w, h) = EMBEDDED_SIZE;
+    let aspect = w as f32 / h as f32;
+    let avail = ui.available_size();
+    let height = (avail.x / aspect).min(avail.y * 0.5);
+    let size = egui::vec2(height * aspect, height);
+    match &e.texture {
+        Some(t) => {
+            ui.vertical_centered(|ui| ui.image((t.id(), size)));
+        }
+        None => {
+            ui.allocate_ui(size, |ui| {
+                ui.centered_and_justified(|ui| ui.spinner());
+            });
+        }
+    }
+    ui.separator();
+}
+
+/// Tiny line chart of build durations, oldest left; hover for the range.
+fn draw_sparkline(ui: &mut egui::Ui, values: &VecDeque<u128>) {
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(120.0, 16.0), egui::Sense::hover());
+    let max = values.iter().copied().max().unwrap_or(1).max(1) as f32;
+    let step = rect.width() / (BUILD_HISTORY_LEN - 1) as f32;
+    let points: Vec<egui::Pos2> = values
+        .iter()
+        .enumerate()
+        .map(|(i, &v)| {
+            egui::pos2(
+                rect.left() + i as f32 * step,
+                rect.bottom() - v as f32 / max * rect.height(),
+            )
+        })
+        .collect();
+    ui.painter().add(egui::Shape::line(
+        points,
+        egui::Stroke::new(1.0, ui.visuals().text_color()),
+    ));
+    let min = values.iter().copied().min().unwrap_or(0);
+    response.on_hover_text(format!(
+        "last {} builds: {min}–{} ms",
+        values.len(),
+        max as u128
+    ));
+}
+
+/// "Paste <type>" for the component clipboard; true when clicked.
+fn paste_component_button(ui: &mut egui::Ui, clipboard: Option<&ComponentDoc>) -> bool {
+    let label = match clipboard {
+        Some(c) => format!("Paste {}", c.type_id),
+        None => "Paste component".to_string(),
+    };
+    let clicked = ui
+        .add_enabled(clipboard.is_some(), egui::Button::new(label))
+        .on_hover_text("Replace this entity's component of that type, or add it")
+        .on_disabled_hover_text("Right-click a component header and copy it first")
+        .clicked();
+    if clicked {
+        ui.close();
+    }
+    clicked
+}
+
+/// Modal asking whether to save, discard, or cancel; `None` while unanswered.
+fn unsaved_changes_modal(ctx: &egui::Context, id: &str, message: &str) -> Option<UnsavedChoice> {
+    let mut choice = None;
+    let modal = egui::Modal::new(egui::Id::new(id)).show(ctx, |ui| {
+        ui.heading("Unsaved changes");
+        ui.label(message);
+        ui.separator();
+        ui.horizontal(|ui| {
+            if ui.button("Save").clicked() {
+                choice = Some(UnsavedChoice::Save);
+            }
+            if ui.button("Discard").clicked() {
+                choice = Some(UnsavedChoice::Discard);
+            }
+            if ui.button("Cancel").clicked() {
+                choice = Some(UnsavedChoice::Cancel);
+            }
+        });
+    });
+    if modal.should_close() && choice.is_none() {
+        choice = Some(UnsavedChoice::Cancel); // Esc / click outside
+    }
+    choice
+}
+
+/// Editable entity id. When the field loses focus the typed id is returned
+/// for `SceneDoc::rename_entity`; a refused rename shows as an inline warning.
+fn draw_entity_id(
+    ui: &mut egui::Ui,
+    scene: &SceneDoc,
+    sel: usize,
+    rename: &mut Option<(usize, String)>,
+    rename_error: Option<&str>,
+) -> Option<(usize, String)> {
+    let mut renamed = None;
+    let mut buf = match rename {
+        Some((target, buf)) if *target == sel => buf.clone(),
+        _ => scene.entities[sel].id.clone(),
+    };
+
+    let resp = ui
+        .horizontal(|ui| {
+            ui.label("Entity:");
+            ui.add(
+                egui::TextEdit::singleline(&mut buf)
+                    .font(egui::TextStyle::Monospace)
+                    .desired_width(200.0),
+            )
+        })
+        .inner;
+
+    if resp.has_focus() {
+        // remember the target so a selection change mid-edit renames the right entity
+        let target = rename.as_ref().map_or(sel, |(t, _)| *t);
+        *rename = Some((target, buf));
+    } else if resp.lost_focus() {
+        renamed = rename.take();
+    }
+
+    if let Some(err) = rename_error {
+        ui.colored_label(ui.visuals().warn_fg_color, format!("⚠ {err}"));
+    }
+    renamed
+}
+
+/// Parent picker. Only entities that wouldn't form a cycle are offered.
+/// Returns the new parent if the user picked a different one.
+fn draw_parent(ui: &mut egui::Ui, scene: &SceneDoc, sel: usize) -> Option<Option<String>> {
+    let current = scene.entities[sel].parent.clone();
+    let mut choice = current.clone();
+    ui.horizontal(|ui| {
+        ui.label("Parent:");
+        ComboBox::from_id_salt("entity_parent")
+            .selected_text(choice.as_deref().unwrap_or("(none)"))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut choice, None, "(none)");
+                for (i, e) in scene.entities.iter().enumerate() {
+                    if !scene.is_ancestor(sel, i) {
+                        ui.selectable_value(&mut choice, Some(e.id.clone()), &e.id);
+                    }
+                }
+            });
+    });
+    (choice != current).then_some(choice)
+}
+
+// ================== Typed inspectors ==================
+// Each returns true when the user edited a value this frame.
+
+/// Numeric field shared by every coordinate/size input: drag to nudge,
+/// click to type an exact value (applied on Enter or focus loss), and at
+/// most three decimals shown so float noise like `1.2999999` stays hidden.
+fn coord_drag(ui: &mut egui::Ui, value: &mut f32, label: &str) -> egui::Response {
+    ui.add(
+        DragValue::new(value)
+            .speed(0.05)
+            .max_decimals(3)
+            .update_while_editing(false)
+            .prefix(format!("{label} ")),
+    )
+}
+
+/// `coord_drag` for a mesh size: edits are raised to `MIN_MESH_DIM`.
+/// `clamped` is set while the value sits below the minimum, including a
+/// too-small value loaded from the file.
+fn dim_drag(ui: &mut egui::Ui, value: &mut f32, label: &str, clamped: &mut bool) -> bool {
+    let changed = coord_drag(ui, value, label).changed();
+    if *value < MIN_MESH_DIM || value.is_nan() {
+        *clamped = true;
+        if changed {
+            *value = MIN_MESH_DIM;
+        }
     }
+    changed
+}
+
+/// Round `v` to the nearest multiple of `step`.
+fn snap_to(v: f32, step: f32) -> f32 {
+    (v / step).round() * step
+}
+
+/// `snap`: grid step to round translation edits to, if snapping is on.
+/// `reset` is set when the Reset button is clicked; the caller applies
+/// `reset_transform` so the change can be recorded as one undo step.
+fn draw_transform(
+    ui: &mut egui::Ui,
+    d: &mut CompData,
+    snap: Option<f32>,
+    clip: &mut Option<(f32, f32, f32)>,
+    reset: &mut bool,
+) -> bool {
+    let mut changed = false;
+    // look_at / rot_x_deg have no editor yet
+    ui.vertical(|ui| {
+        ui.label("translation");
+        let mut t = d.translation.unwrap_or((0.0, 0.0, 0.0));
+        let mut pasted = false;
+        ui.horizontal(|ui| {
+            let fields = [
+                coord_drag(ui, &mut t.0, "x"),
+                coord_drag(ui, &mut t.1, "y"),
+                coord_drag(ui, &mut t.2, "z"),
+            ];
+            changed |= fields.iter().any(|r| r.changed());
+            for r in &fields {
+                pasted |= vec3_context_menu(r, &mut t, clip);
+            }
+        });
+        if changed && let Some(step) = snap {
+            t = (snap_to(t.0, step), snap_to(t.1, step), snap_to(t.2, step));
+        }
+        changed |= pasted;
+        d.translation = Some(t);
+
+        // rotation/scale stay None (identity, not serialized) until edited
+        ui.horizontal(|ui| {
+            let mut yaw = d.rot_y_deg.unwrap_or(0.0);
+            if ui
+                .add(
+                    DragValue::new(&mut yaw)
+                        .speed(1.0)
+                        .max_decimals(2)
+                        .update_while_editing(false)
+                        .prefix("yaw ")
+                        .suffix("°"),
+                )
+                .changed()
+            {
+                d.rot_y_deg = Some(yaw);
+                changed = true;
+            }
+            changed |= reset_button(ui, &mut d.rot_y_deg);
+        });
+        ui.label("scale");
+        ui.horizontal(|ui| {
+            let mut sc = d.scale.unwrap_or((1.0, 1.0, 1.0));
+            let fields = [
+                coord_drag(ui, &mut sc.0, "x"),
+                coord_drag(ui, &mut sc.1, "y"),
+                coord_drag(ui, &mut sc.2, "z"),
+            ];
+            let mut edited = fields.iter().any(|r| r.changed());
+            for r in &fields {
+                edited |= vec3_context_menu(r, &mut sc, clip);
+            }
+            if edited {
+                d.scale = Some(sc);
+                changed = true;
+            }
+            changed |= reset_button(ui, &mut d.scale);
+        });
+        if ui
+            .button("Reset")
+            .on_hover_text("Translation 0, no rotation, scale 1")
+            .clicked()
+        {
+            *reset = true;
+        }
+    });
+    changed
 }
 
-// ================== Typed inspectors ==================
+/// Back to the identity transform: origin, unrotated, unit scale.
+fn reset_transform(d: &mut CompData) {
+    d.translation = Some((0.0, 0.0, 0.0));
+    d.rot_x_deg = None;
+    d.rot_y_deg = None;
+    d.look_at = None;
+    d.scale = None;
+}
 
-fn draw_transform(ui: &mut egui::Ui, d: &mut CompData) {
-    // Translation only (rotation & look_at removed for now)
-    ui.vertical(|ui| {
-        ui.label("translation");
-        let mut t = d.translation.unwrap_or((0.0, 0.0, 0.0));
-        ui.horizontal(|ui| {
-            ui.add(DragValue::new(&mut t.0).speed(0.1).prefix("x "));
-            ui.add(DragValue::new(&mut t.1).speed(0.1).prefix("y "));
-            ui.add(DragValue::new(&mut t.2).speed(0.1).prefix("z "));
-        });
-        d.translation = Some(t);
+/// Right-click menu on a vector field to copy/paste the whole (x, y, z).
+/// Returns true when a paste changed `v`.
+fn vec3_context_menu(
+    resp: &egui::Response,
+    v: &mut (f32, f32, f32),
+    clip: &mut Option<(f32, f32, f32)>,
+) -> bool {
+    let mut pasted = false;
+    resp.context_menu(|ui| {
+        if ui.button("Copy (x, y, z)").clicked() {
+            *clip = Some(*v);
+            ui.close();
+        }
+        if ui
+            .add_enabled(clip.is_some(), egui::Button::new("Paste (x, y, z)"))
+            .clicked()
+        {
+            if let Some(c) = *clip {
+                pasted = c != *v;
+                *v = c;
+            }
+            ui.close();
+        }
     });
+    pasted
 }
 
-fn draw_mesh3d(ui: &mut egui::Ui, d: &mut CompData) {
+/// `root` is the project root; asset paths are stored relative to it.
+fn draw_mesh3d(ui: &mut egui::Ui, d: &mut CompData, root: &std::path::Path) -> bool {
+    let mut changed = false;
+    let mut clamped = false;
     let mut shape = d.shape.clone().unwrap_or_else(|| "Cuboid".into());
     ComboBox::from_label("shape")
         .selected_text(&shape)
         .show_ui(ui, |ui| {
-            ui.selectable_value(&mut shape, "Circle".into(), "Circle");
-            ui.selectable_value(&mut shape, "Cuboid".into(), "Cuboid");
+            changed |= ui
+                .selectable_value(&mut shape, "Circle".into(), "Circle")
+                .changed();
+            changed |= ui
+                .selectable_value(&mut shape, "Cuboid".into(), "Cuboid")
+                .changed();
+            changed |= ui
+                .selectable_value(&mut shape, "Asset".into(), "Asset")
+                .changed();
         });
     d.shape = Some(shape.clone());
 
     match shape.as_str() {
         "Circle" => {
             let mut r = d.radius.unwrap_or(1.0);
-            ui.add(DragValue::new(&mut r).speed(0.1).prefix("radius "));
+            changed |= dim_drag(ui, &mut r, "radius", &mut clamped);
             d.radius = Some(r);
             // clear cuboid dims so we don't serialize junk
             d.x = None;
             d.y = None;
             d.z = None;
+            d.mesh_path = None;
+        }
+        "Asset" => {
+            let mut path = d.mesh_path.clone().unwrap_or_default();
+            ui.horizontal(|ui| {
+                changed |= ui
+                    .add(
+                        egui::TextEdit::singleline(&mut path)
+                            .hint_text("assets/models/thing.glb")
+                            .desired_width(180.0),
+                    )
+                    .changed();
+                if ui.button("Browse…").clicked()
+                    && let Some(picked) = rfd::FileDialog::new()
+                        .set_directory(root)
+                        .add_filter("glTF", &["glb", "gltf"])
+                        .pick_file()
+                {
+                    // relative (with forward slashes) so the scene stays portable;
+                    // files outside the project keep their absolute path
+                    path = match picked.strip_prefix(root) {
+                        Ok(rel) => rel.to_string_lossy().replace('\\', "/"),
+                        Err(_) => picked.to_string_lossy().into_owned(),
+                    };
+                    changed = true;
+                }
+            });
+            d.mesh_path = Some(path);
+            d.radius = None;
+            d.x = None;
+            d.y = None;
+            d.z = None;
         }
         _ => {
             let mut x = d.x.unwrap_or(1.0);
             let mut y = d.y.unwrap_or(1.0);
             let mut z = d.z.unwrap_or(1.0);
             ui.horizontal(|ui| {
-                ui.add(DragValue::new(&mut x).speed(0.1).prefix("x "));
-                ui.add(DragValue::new(&mut y).speed(0.1).prefix("y "));
-                ui.add(DragValue::new(&mut z).speed(0.1).prefix("z "));
+                changed |= dim_drag(ui, &mut x, "x", &mut clamped);
+                changed |= dim_drag(ui, &mut y, "y", &mut clamped);
+                changed |= dim_drag(ui, &mut z, "z", &mut clamped);
             });
             d.x = Some(x);
             d.y = Some(y);
             d.z = Some(z);
             d.radius = None;
+            d.mesh_path = None;
+        }
+    }
+    if clamped {
+        ui.colored_label(
+            ui.visuals().warn_fg_color,
+            format!("sizes below {MIN_MESH_DIM} are clamped"),
+        );
+    }
+    changed
+}
+
+fn draw_material3d(ui: &mut egui::Ui, d: &mut CompData) -> bool {
+    // CompData.color is linear and unmultiplied (what Color::linear_rgba
+    // expects); egui's Rgba is linear too but premultiplied, so only the
+    // alpha needs converting. The picker shows it gamma-encoded, as Bevy does.
+    let (r, g, b, a) = d.color.unwrap_or((1.0, 1.0, 1.0, 1.0));
+    let mut rgba = Rgba::from_rgba_unmultiplied(r, g, b, a);
+    let mut changed =
+        egui::color_picker::color_edit_button_rgba(ui, &mut rgba, Alpha::OnlyBlend).changed();
+    let [r, g, b, a] = rgba.to_rgba_unmultiplied();
+    d.color = Some((r, g, b, a));
+
+    // PBR extras stay None (Bevy default, not serialized) until touched
+    changed |= optional_slider(ui, &mut d.metallic, 0.0, "metallic");
+    changed |= optional_slider(ui, &mut d.roughness, 0.5, "roughness");
+
+    ui.horizontal(|ui| {
+        let (er, eg, eb) = d.emissive.unwrap_or((0.0, 0.0, 0.0));
+        let mut rgb = [er, eg, eb];
+        if egui::color_picker::color_edit_button_rgb(ui, &mut rgb).changed() {
+            d.emissive = Some((rgb[0], rgb[1], rgb[2]));
+            changed = true;
+        }
+        ui.label("emissive");
+        changed |= reset_button(ui, &mut d.emissive);
+    });
+    changed
+}
+
+/// 0..1 slider for an optional field; `default` is shown while it's None.
+fn optional_slider(ui: &mut egui::Ui, v: &mut Option<f32>, default: f32, label: &str) -> bool {
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        let mut x = v.unwrap_or(default);
+        if ui
+            .add(egui::Slider::new(&mut x, 0.0..=1.0).text(label))
+            .changed()
+        {
+            *v = Some(x);
+            changed = true;
+        }
+        changed |= reset_button(ui, v);
+    });
+    changed
+}
+
+/// Small "↺" that puts an optional field back to None (Bevy default).
+fn reset_button<T>(ui: &mut egui::Ui, v: &mut Option<T>) -> bool {
+    let clicked = ui
+        .add_enabled(v.is_some(), egui::Button::new("↺").small())
+        .on_hover_text("Reset to default")
+        .clicked();
+    if clicked {
+        *v = None;
+    }
+    clicked
+}
+
+/// Matches `bevy::pbr::PointLight::default().intensity` (lumens).
+const DEFAULT_LIGHT_INTENSITY: f32 = 1_000_000.0;
+
+fn draw_point_light(ui: &mut egui::Ui, d: &mut CompData) -> bool {
+    let mut sh = d.shadows_enabled.unwrap_or(false);
+    let mut changed = ui.checkbox(&mut sh, "shadows_enabled").changed();
+    d.shadows_enabled = Some(sh);
+
+    // intensity/color stay None until edited so untouched lights keep Bevy's defaults
+    let mut intensity = d.intensity.unwrap_or(DEFAULT_LIGHT_INTENSITY);
+    if ui
+        .add(
+            DragValue::new(&mut intensity)
+                .speed(1000.0)
+                .range(0.0..=f32::MAX)
+                .prefix("intensity ")
+                .suffix(" lm"),
+        )
+        .changed()
+    {
+        d.intensity = Some(intensity);
+        changed = true;
+    }
+
+    let (r, g, b, a) = d.light_color.unwrap_or((1.0, 1.0, 1.0, 1.0));
+    let mut rgba = Rgba::from_rgba_unmultiplied(r, g, b, a);
+    ui.horizontal(|ui| {
+        ui.label("color");
+        if egui::color_picker::color_edit_button_rgba(ui, &mut rgba, Alpha::Opaque).changed() {
+            let [r, g, b, a] = rgba.to_rgba_unmultiplied();
+            d.light_color = Some((r, g, b, a));
+            changed = true;
+        }
+    });
+    changed
+}
+
+/// Matches `bevy::render::camera::PerspectiveProjection::default().fov` (radians: π/4).
+const DEFAULT_FOV_DEG: f32 = 45.0;
+
+fn draw_camera3d(ui: &mut egui::Ui, d: &mut CompData) -> bool {
+    let mut changed = false;
+
+    let mut projection = d.projection.clone().unwrap_or_else(|| "Perspective".into());
+    ComboBox::from_label("projection")
+        .selected_text(&projection)
+        .show_ui(ui, |ui| {
+            for p in ["Perspective", "Orthographic"] {
+                changed |= ui
+                    .selectable_value(&mut projection, p.to_string(), p)
+                    .changed();
+            }
+        });
+    if changed {
+        d.projection = Some(projection.clone());
+    }
+
+    if projection == "Perspective" {
+        ui.horizontal(|ui| {
+            let mut fov = d.fov_deg.unwrap_or(DEFAULT_FOV_DEG);
+            if ui
+                .add(
+                    DragValue::new(&mut fov)
+                        .speed(0.5)
+                        .range(1.0..=179.0)
+                        .prefix("fov ")
+                        .suffix("°"),
+                )
+                .changed()
+            {
+                d.fov_deg = Some(fov);
+                changed = true;
+            }
+            changed |= reset_button(ui, &mut d.fov_deg);
+        });
+    }
+
+    // unmultiplied, like Material3d's color
+    ui.horizontal(|ui| {
+        let (r, g, b, a) = d.clear_color.unwrap_or((0.0, 0.0, 0.0, 1.0));
+        let mut rgba = Rgba::from_rgba_unmultiplied(r, g, b, a);
+        if egui::color_picker::color_edit_button_rgba(ui, &mut rgba, Alpha::OnlyBlend).changed() {
+            let [r, g, b, a] = rgba.to_rgba_unmultiplied();
+            d.clear_color = Some((r, g, b, a));
+            changed = true;
+        }
+        ui.label("clear color");
+        changed |= reset_button(ui, &mut d.clear_color);
+    });
+    changed
+}
+
+// ================== 2D top-down preview (egui painter) ==================
+
+#[derive(Clone)]
+struct DrawCmd {
+    entity: usize, // index into scene.entities
+    kind: DrawKind,
+    pos: egui::Vec2,       // world position in the view plane (see ViewPlane)
+    size: egui::Vec2,      // world size, scale applied (for circle: the two radii)
+    color: egui::Color32,  // sRGBA
+    depth: f32,            // toward the viewer: y from the top, z from the front
+    yaw: f32,              // radians about Y; shapes are drawn rotated by it
+    label: Option<String>, // drawn on top, e.g. an asset mesh's file name
+}
+
+/// Depth cue for the 2D view: shapes grow with `depth` and fade toward the
+/// background the further they sit behind the nearest entity.
+/// Display only; hit testing follows the drawn size.
+#[derive(Clone, Copy)]
+struct DepthCue {
+    enabled: bool,
+    grow_per_unit: f32, // size factor gained per world unit above y = 0
+    fade_per_unit: f32, // blend toward the background per unit below the top
+}
+
+impl Default for DepthCue {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            grow_per_unit: 0.04,
+            fade_per_unit: 0.08,
+        }
+    }
+}
+
+impl DepthCue {
+    fn apply(self, cmds: &mut [DrawCmd], background: egui::Color32) {
+        if !self.enabled {
+            return;
+        }
+        let top = cmds
+            .iter()
+            .map(|c| c.depth)
+            .fold(f32::NEG_INFINITY, f32::max);
+        for cmd in cmds {
+            if matches!(cmd.kind, DrawKind::Circle | DrawKind::Rect) {
+                cmd.size *= (1.0 + cmd.depth * self.grow_per_unit).clamp(0.5, 2.0);
+            }
+            let fade = ((top - cmd.depth) * self.fade_per_unit).clamp(0.0, 0.6);
+            cmd.color = cmd.color.lerp_to_gamma(background, fade);
+        }
+    }
+}
+
+/// The 2D view's framing and tools, kept across frames.
+struct Viewport {
+    offset: egui::Vec2,    // world-space pan (in "meters")
+    zoom: f32,             // screen pixels per world unit
+    focus_requested: bool, // frame selection (or whole scene) on next draw
+    snap_enabled: bool,
+    snap_step: f32, // world units; translation edits round to multiples of this
+    gizmo_mode: GizmoMode,
+    drag: Option<EntityDrag>,
+    ruler: Option<(egui::Vec2, egui::Vec2)>, // last measurement, world xz endpoints
+    depth_cue: DepthCue,
+    show_minimap: bool, // overview inset in the viewport corner
+    show_ids: bool,     // entity ids drawn over their shapes
+    plane: ViewPlane,
+}
+
+impl Viewport {
+    /// Step translation edits round to, when snapping is on.
+    fn snap(&self) -> Option<f32> {
+        self.snap_enabled.then_some(self.snap_step)
+    }
+}
+
+/// What `draw_scene_preview` did to the scene this frame.
+enum ViewportEdit {
+    None,
+    /// A drag changed transforms; its undo step comes when it ends.
+    Live,
+    /// A drag ended; the scene as it was before it.
+    Finished(SceneDoc),
+}
+
+/// An entity being moved/rotated/scaled by dragging in the viewport.
+struct EntityDrag {
+    entity: usize,
+    kind: DragKind,
+    press_world: egui::Vec2, // pointer position at press, world xz
+    before: SceneDoc,        // undo snapshot
+}
+
+enum DragKind {
+    Move {
+        starts: Vec<(usize, (f32, f32, f32))>, // every moved entity's world translation at press
+    },
+    Rotate {
+        center: egui::Vec2, // world xz
+        start_deg: f32,
+        press_angle: f32, // radians, pointer around center at press
+    },
+    Scale {
+        axis: ScaleAxis,
+        center: egui::Vec2, // world xz
+        yaw: f32,
+        start: (f32, f32, f32),
+        press_dist: f32, // pointer offset along the axis at press
+    },
+}
+
+#[derive(Clone, Copy)]
+enum DrawKind {
+    Circle,
+    Rect,
+    Light, // fixed-size marker, size unused when drawing
+    /// Fixed-size frustum wedge; `dir` is the unit view direction in xz.
+    Camera {
+        dir: egui::Vec2,
+        fov_deg: f32,
+    },
+}
+
+/// Linear, unmultiplied RGBA tuple (as stored in `CompData`) to egui color.
+/// `Color32` holds sRGB bytes, so the channels are gamma-encoded on the way;
+/// scaling the linear values straight to 0..255 would draw them too dark.
+fn color32_from_rgba((r, g, b, a): (f32, f32, f32, f32)) -> egui::Color32 {
+    Rgba::from_rgba_unmultiplied(r, g, b, a).into()
+}
+
+/// World plane shown by the 2D view. Screen +x is world +x in both; screen
+/// +y (down) is world +z from the top and world -y from the front.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+enum ViewPlane {
+    #[default]
+    Top,
+    Front, // for scenes built upward rather than along the ground
+}
+
+impl ViewPlane {
+    /// Position in the view plane of world point `(x, y, z)`, and its depth
+    /// toward the viewer.
+    fn project(self, (x, y, z): (f32, f32, f32)) -> (egui::Vec2, f32) {
+        match self {
+            ViewPlane::Top => (egui::vec2(x, z), y),
+            ViewPlane::Front => (egui::vec2(x, -y), z),
+        }
+    }
+
+    /// Write view-plane position `v` into the matching components of `t`.
+    fn unproject(self, v: egui::Vec2, t: &mut (f32, f32, f32)) {
+        t.0 = v.x;
+        match self {
+            ViewPlane::Top => t.2 = v.y,
+            ViewPlane::Front => t.1 = -v.y,
+        }
+    }
+
+    /// World axis along screen y, and its sign there (+1 = pointing down).
+    fn vertical_axis(self) -> (&'static str, f32) {
+        match self {
+            ViewPlane::Top => ("z", 1.0),
+            ViewPlane::Front => ("y", -1.0),
+        }
+    }
+}
+
+fn gather_draw_cmds(scene: &crate::project::SceneDoc, plane: ViewPlane) -> Vec<DrawCmd> {
+    use egui::Color32;
+    let mut cmds = Vec::new();
+
+    for (entity, ent) in scene.entities.iter().enumerate() {
+        if scene.hidden_in_tree(entity) {
+            continue;
+        }
+        let mut world = (0.0f32, 0.0f32, 0.0f32);
+
+        let mut color = Color32::from_rgba_premultiplied(200, 200, 200, 255);
+        let mut shape: Option<&str> = None;
+        let mut radius: Option<f32> = None;
+        let mut cuboid: Option<(f32, f32)> = None; // dims in the view plane
+        let mut mesh_path: Option<&str> = None;
+        let mut light_color: Option<Color32> = None;
+        let mut look_at: Option<(f32, f32, f32)> = None;
+        let mut camera_fov: Option<f32> = None;
+        let mut yaw = 0.0f32;
+        let mut scale = egui::vec2(1.0, 1.0); // in the view plane
+
+        for (ci, comp) in ent.components.iter().enumerate() {
+            // first component of each type wins (see EntityDoc::component)
+            if ent.is_duplicate_component(ci) {
+                continue;
+            }
+            match comp.type_id.as_str() {
+                "Transform" => {
+                    // children are relative to their parent
+                    if comp.data.translation.is_some()
+                        && let Some(t) = scene.world_translation(entity)
+                    {
+                        world = t;
+                    }
+                    look_at = comp.data.look_at;
+                    // like the preview: look_at overrides Euler rotation
+                    if comp.data.look_at.is_none() {
+                        yaw = comp.data.rot_y_deg.unwrap_or(0.0).to_radians();
+                    }
+                    if let Some((x, y, z)) = comp.data.scale {
+                        scale = match plane {
+                            ViewPlane::Top => egui::vec2(x, z),
+                            ViewPlane::Front => egui::vec2(x, y),
+                        };
+                    }
+                }
+                "Camera3d" => {
+                    // orthographic has no fov; draw it as a narrow wedge
+                    camera_fov = Some(match comp.data.projection.as_deref() {
+                        Some("Orthographic") => 10.0,
+                        _ => comp.data.fov_deg.unwrap_or(DEFAULT_FOV_DEG),
+                    });
+                }
+                "Material3d" => {
+                    if let Some(c) = comp.data.color {
+                        color = color32_from_rgba(c);
+                    }
+                }
+                "PointLight" => {
+                    light_color = Some(
+                        comp.data
+                            .light_color
+                            .map(color32_from_rgba)
+                            .unwrap_or(Color32::WHITE),
+                    );
+                }
+                "Mesh3d" => {
+                    if let Some(s) = comp.data.shape.as_deref() {
+                        shape = Some(s);
+                        match s {
+                            "Circle" => {
+                                radius = Some(comp.data.radius.unwrap_or(1.0).max(MIN_MESH_DIM))
+                            }
+                            "Cuboid" => {
+                                let dim = |d: Option<f32>| d.unwrap_or(1.0).max(MIN_MESH_DIM);
+                                let across = match plane {
+                                    ViewPlane::Top => comp.data.z,
+                                    ViewPlane::Front => comp.data.y,
+                                };
+                                cuboid = Some((dim(comp.data.x), dim(across)));
+                            }
+                            "Asset" => mesh_path = comp.data.mesh_path.as_deref(),
+                            _ => {}
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // turned and scaled by the parent chain too, as Bevy does via ChildOf
+        let (parent_yaw, (px, py, pz)) = scene.parent_yaw_scale(entity);
+        yaw += parent_yaw;
+        scale = scale
+            * match plane {
+                ViewPlane::Top => egui::vec2(px, pz),
+                ViewPlane::Front => egui::vec2(px, py),
+            };
+
+        let (pos, depth) = plane.project(world);
+        // Bevy cameras look down -Z (turned by yaw) unless a look_at target is set
+        let forward = look_at
+            .map(|(x, y, z)| (x - world.0, y - world.1, z - world.2))
+            .unwrap_or_else(|| {
+                let v = rotate_xz(egui::vec2(0.0, -1.0), yaw);
+                (v.x, 0.0, v.y)
+            });
+        // yaw turns shapes within the ground plane only
+        if plane == ViewPlane::Front {
+            yaw = 0.0;
+        }
+
+        match shape {
+            Some("Circle") => {
+                let r = radius.unwrap_or(1.0);
+                cmds.push(DrawCmd {
+                    entity,
+                    kind: DrawKind::Circle,
+                    pos,
+                    size: egui::vec2(r, r) * scale,
+                    color,
+                    depth,
+                    yaw,
+                    label: None,
+                });
+            }
+            Some("Cuboid") => {
+                let (w, h) = cuboid.unwrap_or((1.0, 1.0));
+                cmds.push(DrawCmd {
+                    entity,
+                    kind: DrawKind::Rect,
+                    pos,
+                    size: egui::vec2(w, h) * scale,
+                    color,
+                    depth,
+                    yaw,
+                    label: None,
+                });
+            }
+            // can't rasterize a glTF top-down; a unit placeholder with its name
+            Some("Asset") => {
+                let name = mesh_path
+                    .map(|p| {
+                        std::path::Path::new(p)
+                            .file_name()
+                            .map_or(p.to_string(), |n| n.to_string_lossy().into_owned())
+                    })
+                    .unwrap_or_else(|| "(no asset)".into());
+                cmds.push(DrawCmd {
+                    entity,
+                    kind: DrawKind::Rect,
+                    pos,
+                    size: scale,
+                    color,
+                    depth,
+                    yaw,
+                    label: Some(name),
+                });
+            }
+            _ => {}
+        }
+
+        if let Some(color) = light_color {
+            cmds.push(DrawCmd {
+                entity,
+                kind: DrawKind::Light,
+                pos,
+                size: egui::vec2(0.25, 0.25),
+                color,
+                depth,
+                yaw,
+                label: None,
+            });
+        }
+
+        if let Some(fov_deg) = camera_fov {
+            // one looking straight along the view axis is drawn pointing down
+            let (dir, _) = plane.project(forward);
+            let dir = if dir.length() > f32::EPSILON {
+                dir.normalized()
+            } else {
+                egui::vec2(0.0, 1.0)
+            };
+            cmds.push(DrawCmd {
+                entity,
+                kind: DrawKind::Camera { dir, fov_deg },
+                pos,
+                size: egui::vec2(0.25, 0.25),
+                color: Color32::LIGHT_GRAY,
+                depth,
+                yaw,
+                label: None,
+            });
+        }
+    }
+
+    cmds
+}
+
+/// Rotate a world xz vector by `yaw` about +Y, the way Bevy's `rotate_y` does.
+fn rotate_xz(v: egui::Vec2, yaw: f32) -> egui::Vec2 {
+    let (sin, cos) = yaw.sin_cos();
+    egui::vec2(v.x * cos + v.y * sin, -v.x * sin + v.y * cos)
+}
+
+/// World-space (xz) axis-aligned footprint of a draw command.
+fn draw_cmd_bounds(cmd: &DrawCmd) -> egui::Rect {
+    let (sin, cos) = cmd.yaw.sin_cos();
+    let (sin, cos) = (sin.abs(), cos.abs());
+    let half = match cmd.kind {
+        DrawKind::Light | DrawKind::Camera { .. } => cmd.size, // size holds the radius
+        DrawKind::Circle => {
+            let (a, b) = (cmd.size.x, cmd.size.y);
+            egui::vec2(
+                ((a * cos).powi(2) + (b * sin).powi(2)).sqrt(),
+                ((a * sin).powi(2) + (b * cos).powi(2)).sqrt(),
+            )
+        }
+        DrawKind::Rect => {
+            let h = cmd.size * 0.5;
+            egui::vec2(h.x * cos + h.y * sin, h.x * sin + h.y * cos)
+        }
+    };
+    egui::Rect::from_center_size(cmd.pos.to_pos2(), half * 2.0)
+}
+
+/// What "focus" should frame: the selected entity's footprint, or every
+/// drawable entity when nothing is selected. Entities without a mesh fall
+/// back to a unit box around their translation.
+fn focus_bounds(
+    scene: &crate::project::SceneDoc,
+    cmds: &[DrawCmd],
+    selected: Option<usize>,
+    plane: ViewPlane,
+) -> Option<egui::Rect> {
+    let from_cmds = cmds
+        .iter()
+        .filter(|c| selected.is_none_or(|sel| c.entity == sel))
+        .map(draw_cmd_bounds)
+        .reduce(|a, b| a.union(b));
+    if from_cmds.is_some() {
+        return from_cmds;
+    }
+
+    let sel = selected?;
+    scene
+        .entities
+        .get(sel)?
+        .component("Transform")?
+        .data
+        .translation?;
+    let (pos, _) = plane.project(scene.world_translation(sel)?);
+    Some(egui::Rect::from_center_size(
+        pos.to_pos2(),
+        egui::vec2(1.0, 1.0),
+    ))
+}
+
+/// What the inspector asked for this frame, applied once its borrow of the
+/// scene ends.
+enum BulkOp {
+    Nudge,
+    Align(Axis, AlignTo),
+    Distribute(Axis),
+    Duplicate,
+    Delete,
+    ResetTransform(usize),
+    /// The component clipboard onto every selected entity.
+    PasteComponent,
+    Rename(usize, String),
+    SetParent(usize, Option<String>),
+    AddComponent(usize, &'static str),
+    /// Entity, component index.
+    RemoveComponent(usize, usize),
+    AttachScript(usize, AttachedScript),
+    /// Entity, script index.
+    DetachScript(usize, usize),
+}
+
+/// Ground-plane axis for align/distribute.
+#[derive(Clone, Copy)]
+enum Axis {
+    X,
+    Z,
+}
+
+#[derive(Clone, Copy)]
+enum AlignTo {
+    Min,
+    Center,
+    Max,
+}
+
+fn min_max(values: &[f32]) -> (f32, f32) {
+    values
+        .iter()
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), &v| {
+            (lo.min(v), hi.max(v))
+        })
+}
+
+/// Inspector for several selected entities: only edits that make sense for
+/// all of them at once.
+fn draw_multi_selection(
+    ui: &mut egui::Ui,
+    count: usize,
+    nudge: &mut (f32, f32, f32),
+    component_clipboard: Option<&ComponentDoc>,
+) -> Option<BulkOp> {
+    let mut op = None;
+    ui.label(format!("{count} entities selected"));
+    ui.small("Ctrl-click in the hierarchy or viewport to add or remove.");
+    ui.separator();
+    ui.label("move by");
+    ui.horizontal(|ui| {
+        coord_drag(ui, &mut nudge.0, "x");
+        coord_drag(ui, &mut nudge.1, "y");
+        coord_drag(ui, &mut nudge.2, "z");
+        if ui.button("Apply").clicked() {
+            op = Some(BulkOp::Nudge);
+        }
+    });
+    ui.separator();
+    egui::Grid::new("align_grid").num_columns(5).show(ui, |ui| {
+        for (axis, name) in [(Axis::X, "x"), (Axis::Z, "z")] {
+            ui.label(format!("align {name}"));
+            for (to, label) in [
+                (AlignTo::Min, "min"),
+                (AlignTo::Center, "center"),
+                (AlignTo::Max, "max"),
+            ] {
+                if ui.button(label).clicked() {
+                    op = Some(BulkOp::Align(axis, to));
+                }
+            }
+            if ui
+                .add_enabled(count >= 3, egui::Button::new("distribute"))
+                .on_hover_text("Even spacing between the outermost two")
+                .on_disabled_hover_text("Needs three or more entities")
+                .clicked()
+            {
+                op = Some(BulkOp::Distribute(axis));
+            }
+            ui.end_row();
+        }
+    });
+    ui.separator();
+    ui.horizontal(|ui| {
+        if ui.button("Duplicate all").clicked() {
+            op = Some(BulkOp::Duplicate);
+        }
+        if ui.button("Delete all").clicked() {
+            op = Some(BulkOp::Delete);
+        }
+    });
+    if paste_component_button(ui, component_clipboard) {
+        op = Some(BulkOp::PasteComponent);
+    }
+    op
+}
+
+/// Entity ids of the primary and extra selection, to find them again once the
+/// scene is replaced.
+fn selection_ids(
+    scene: Option<&SceneDoc>,
+    primary: Option<usize>,
+    extra: &BTreeSet<usize>,
+) -> (Option<String>, Vec<String>) {
+    let id = |i: usize| Some(scene?.entities.get(i)?.id.clone());
+    (
+        primary.and_then(id),
+        extra.iter().filter_map(|&i| id(i)).collect(),
+    )
+}
+
+/// Indices of the entities `selection_ids` named, in `scene` as it is now;
+/// ids that are gone drop out. The first survivor becomes primary.
+fn reselect_ids(
+    scene: Option<&SceneDoc>,
+    (primary, extra): (Option<String>, Vec<String>),
+) -> (Option<usize>, BTreeSet<usize>) {
+    let Some(scene) = scene else {
+        return (None, BTreeSet::new());
+    };
+    let find = |id: &String| scene.entities.iter().position(|e| e.id == *id);
+    let mut extra: BTreeSet<usize> = extra.iter().filter_map(find).collect();
+    let primary = primary
+        .as_ref()
+        .and_then(find)
+        .or_else(|| extra.pop_first());
+    if let Some(p) = primary {
+        extra.remove(&p);
+    }
+    (primary, extra)
+}
+
+/// Ctrl-click on `i`: add it to the selection as the primary entity, or take
+/// it out (the most recent addition then becomes primary).
+fn toggle_selection(primary: &mut Option<usize>, extra: &mut BTreeSet<usize>, i: usize) {
+    if *primary == Some(i) {
+        *primary = extra.pop_last();
+    } else if !extra.remove(&i)
+        && let Some(prev) = primary.replace(i)
+    {
+        extra.insert(prev);
+    }
+}
+
+/// Center the view on `bounds` and zoom so it fills about half the viewport.
+fn frame_bounds(
+    bounds: egui::Rect,
+    rect: egui::Rect,
+    view_offset: &mut egui::Vec2,
+    view_zoom: &mut f32,
+) {
+    const FILL: f32 = 0.5;
+    let size = bounds.size().max(egui::vec2(0.5, 0.5)); // avoid zooming into a point
+    let zoom = (rect.width() / size.x).min(rect.height() / size.y) * FILL;
+    *view_zoom = zoom.clamp(10.0, 400.0);
+    *view_offset = bounds.center().to_vec2();
+}
+
+/// Draw the top-down preview and handle its input. Dragging an entity moves
+/// it (and the rest of the selection) in xz, Y untouched; dragging empty
+/// space pans. `extra` holds Ctrl-click additions to `selected`.
+///
+/// Returns what a manipulation did to the scene this frame.
+fn draw_scene_preview(
+    ui: &mut egui::Ui,
+    scene: &mut SceneDoc,
+    config: &ProjectConfig,
+    selected: &mut Option<usize>,
+    extra: &mut BTreeSet<usize>,
+    view: &mut Viewport,
+) -> ViewportEdit {
+    use std::cmp::Ordering;
+
+    let (snap, gizmo, plane) = (view.snap(), view.gizmo_mode, view.plane);
+    let (depth_cue, show_minimap, show_ids) = (view.depth_cue, view.show_minimap, view.show_ids);
+    let Viewport {
+        offset: view_offset,
+        zoom: view_zoom,
+        focus_requested,
+        drag,
+        ruler,
+        ..
+    } = view;
+
+    // rotate/scale handles edit yaw and ground-plane scale, shown only from the top
+    let gizmo = match (plane, gizmo) {
+        (ViewPlane::Front, GizmoMode::Rotate | GizmoMode::Scale) => GizmoMode::Move,
+        _ => gizmo,
+    };
+
+    // Panel area
+    let avail = ui.available_size();
+    let (response, painter) = ui.allocate_painter(avail, egui::Sense::click_and_drag());
+
+    // Gather draw commands from scene
+    let mut cmds = gather_draw_cmds(scene, plane);
+    depth_cue.apply(&mut cmds, ui.visuals().extreme_bg_color);
+
+    // 🔹 Depth sort: lower Y first, higher Y last (so higher objects draw on top)
+    cmds.sort_by(|a, b| a.depth.partial_cmp(&b.depth).unwrap_or(Ordering::Equal));
+
+    // Minimap on top of the view: its hits shadow the viewport's, and a
+    // click or drag there recenters the main view
+    let minimap = show_minimap
+        .then(|| Minimap::fit(&cmds, response.rect))
+        .flatten();
+    if let Some(map) = &minimap {
+        let hit = ui.interact(
+            map.rect,
+            response.id.with("minimap"),
+            egui::Sense::click_and_drag(),
+        );
+        if let Some(pos) = hit.interact_pointer_pos() {
+            *view_offset = map.to_world(pos);
         }
     }
-}
-
-fn draw_material3d(ui: &mut egui::Ui, d: &mut CompData) {
-    let (mut r, mut g, mut b, mut a) = d.color.unwrap_or((1.0, 1.0, 1.0, 1.0));
-    let mut rgba = Rgba::from_rgba_premultiplied(r, g, b, a);
-    egui::color_picker::color_edit_button_rgba(ui, &mut rgba, Alpha::Opaque);
-    r = rgba.r();
-    g = rgba.g();
-    b = rgba.b();
-    a = rgba.a();
-    d.color = Some((r, g, b, a));
-}
 
-fn draw_point_light(ui: &mut egui::Ui, d: &mut CompData) {
-    let mut sh = d.shadows_enabled.unwrap_or(false);
-    ui.checkbox(&mut sh, "shadows_enabled");
-    d.shadows_enabled = Some(sh);
-}
+    // Frame selection / whole scene on request
+    if std::mem::take(focus_requested)
+        && let Some(bounds) = focus_bounds(scene, &cmds, *selected, plane)
+    {
+        frame_bounds(bounds, response.rect, view_offset, view_zoom);
+    }
 
-// ================== 2D top-down preview (egui painter) ==================
+    // Mouse wheel zoom:
+    if response.hovered()
+        && let Some(scroll) = ui.input(|i| i.smooth_scroll_delta.y).into()
+    {
+        // zoom around mouse
+        let zoom_factor = (1.0 + scroll * -0.001).clamp(0.5, 4.0);
+        let old_zoom = *view_zoom;
+        let new_zoom = (old_zoom * zoom_factor).clamp(10.0, 400.0);
 
-#[derive(Clone, Copy)]
-struct DrawCmd {
-    kind: DrawKind,
-    pos: egui::Vec2,      // world xz
-    size: egui::Vec2,     // world size (for circle: x = radius, y = radius)
-    color: egui::Color32, // sRGBA
-    height_y: f32,
-}
+        // keep world point under cursor stable
+        let mouse_pos = ui.input(|i| i.pointer.hover_pos());
+        if let Some(mp) = mouse_pos {
+            let world_before = screen_to_world(mp, response.rect, *view_offset, old_zoom);
+            *view_zoom = new_zoom;
+            let world_after = screen_to_world(mp, response.rect, *view_offset, new_zoom);
+            *view_offset += world_after - world_before;
+        } else {
+            *view_zoom = new_zoom;
+        }
+    }
 
-#[derive(Clone, Copy)]
-enum DrawKind {
-    Circle,
-    Rect,
-}
+    let to_world = |p: egui::Pos2| screen_to_world(p, response.rect, *view_offset, *view_zoom);
+    let gizmo_at = |scene: &SceneDoc, cmds: &[DrawCmd], sel: Option<usize>, offset, zoom| {
+        let sel = sel?;
+        gizmo_geom(scene, cmds, sel, response.rect, offset, zoom).map(|g| (sel, g))
+    };
 
-fn gather_draw_cmds(scene: &crate::project::SceneDoc) -> Vec<DrawCmd> {
-    use egui::Color32;
-    let mut cmds = Vec::new();
+    // Click selects (empty space clears the selection); Ctrl-click toggles
+    if response.clicked()
+        && let Some(pos) = response.interact_pointer_pos()
+    {
+        let hit = hit_test(&cmds, pos, response.rect, *view_offset, *view_zoom);
+        match hit {
+            Some(i) if ui.input(|i| i.modifiers.command) => toggle_selection(selected, extra, i),
+            _ => {
+                *selected = hit;
+                extra.clear();
+            }
+        }
+    }
 
-    for ent in &scene.entities {
-        let mut pos_xz = (0.0f32, 0.0f32);
-        let mut pos_y = 0.0f32; // <-- NEW
+    // Press: grab a gizmo handle of the selection, or (Move) an entity
+    if response.drag_started()
+        && let Some(press) = ui.input(|i| i.pointer.press_origin())
+    {
+        let press_world = to_world(press);
+        let grabbed = match (
+            gizmo,
+            gizmo_at(scene, &cmds, *selected, *view_offset, *view_zoom),
+        ) {
+            (GizmoMode::Rotate, Some((sel, g)))
+                if (g.center.distance(press) - g.ring_r).abs() <= GIZMO_GRAB_PX =>
+            {
+                Some((
+                    sel,
+                    DragKind::Rotate {
+                        center: g.center_world,
+                        start_deg: g.rot_y_deg,
+                        press_angle: (press - g.center).angle(),
+                    },
+                ))
+            }
+            (GizmoMode::Scale, Some((sel, g))) => [
+                (ScaleAxis::X, g.handle_x),
+                (ScaleAxis::Z, g.handle_z),
+                (ScaleAxis::Uniform, g.handle_xz),
+            ]
+            .into_iter()
+            .filter(|(_, h)| h.distance(press) <= GIZMO_GRAB_PX)
+            .min_by(|a, b| a.1.distance(press).total_cmp(&b.1.distance(press)))
+            .map(|(axis, _)| {
+                (
+                    sel,
+                    DragKind::Scale {
+                        axis,
+                        center: g.center_world,
+                        yaw: g.yaw,
+                        start: g.scale,
+                        press_dist: axis.measure(press_world - g.center_world, g.yaw),
+                    },
+                )
+            }),
+            (GizmoMode::Move, _) => hit_test(&cmds, press, response.rect, *view_offset, *view_zoom)
+                .and_then(|entity| {
+                    // grabbing part of the selection moves all of it
+                    if *selected != Some(entity) && !extra.contains(&entity) {
+                        *selected = Some(entity);
+                        extra.clear();
+                    }
+                    // locked entities stay put, and grabbing one moves nothing
+                    let translation = |i: usize| {
+                        let ent = &scene.entities[i];
+                        if ent.is_locked() {
+                            return None;
+                        }
+                        ent.component("Transform")?.data.translation?;
+                        Some((i, scene.world_translation(i)?))
+                    };
+                    translation(entity)?;
+                    let starts = extra
+                        .iter()
+                        .copied()
+                        .chain(*selected)
+                        .filter_map(translation)
+                        .collect();
+                    Some((entity, DragKind::Move { starts }))
+                }),
+            _ => None,
+        };
+        if let Some((entity, kind)) = grabbed {
+            // the grabbed entity becomes primary without leaving the selection
+            if *selected != Some(entity) {
+                extra.remove(&entity);
+                if let Some(prev) = selected.replace(entity) {
+                    extra.insert(prev);
+                }
+            }
+            *drag = Some(EntityDrag {
+                entity,
+                kind,
+                press_world,
+                before: scene.clone(),
+            });
+        }
+    }
 
-        let mut color = Color32::from_rgba_premultiplied(200, 200, 200, 255);
-        let mut shape: Option<&str> = None;
-        let mut radius: Option<f32> = None;
-        let mut cuboid_xz: Option<(f32, f32)> = None;
+    // Ruler: a primary drag measures from the press point (other buttons still pan)
+    let measuring =
+        gizmo == GizmoMode::Measure && response.dragged_by(egui::PointerButton::Primary);
+    if measuring
+        && response.drag_started()
+        && let Some(press) = ui.input(|i| i.pointer.press_origin())
+    {
+        let mut start = to_world(press);
+        if let Some(step) = snap {
+            start = egui::vec2(snap_to(start.x, step), snap_to(start.y, step));
+        }
+        *ruler = Some((start, start));
+    }
 
-        for comp in &ent.components {
-            match comp.type_id.as_str() {
-                "Transform" => {
-                    if let Some((x, y, z)) = comp.data.translation {
-                        pos_xz = (x, z);
-                        pos_y = y; // <-- NEW
+    let mut edit = ViewportEdit::None;
+    if let Some(d) = drag.as_ref() {
+        if response.dragged()
+            && let Some(pos) = response.interact_pointer_pos()
+        {
+            let world = to_world(pos);
+            fn transform(scene: &mut SceneDoc, i: usize) -> Option<&mut CompData> {
+                scene
+                    .entities
+                    .get_mut(i)
+                    .and_then(|e| e.component_mut("Transform"))
+                    .map(|c| &mut c.data)
+            }
+            match &d.kind {
+                DragKind::Move { starts } => {
+                    let delta = world - d.press_world;
+                    for &(i, start) in starts {
+                        let mut v = plane.project(start).0 + delta;
+                        if let Some(step) = snap {
+                            v = egui::vec2(snap_to(v.x, step), snap_to(v.y, step));
+                        }
+                        // moved in world space, written back relative to the parent
+                        let mut world = start;
+                        plane.unproject(v, &mut world);
+                        let local = scene.world_to_parent(i, world);
+                        if let Some(t) = transform(scene, i).and_then(|d| d.translation.as_mut()) {
+                            *t = local;
+                        }
                     }
                 }
-                "Material3d" => {
-                    if let Some((r, g, b, a)) = comp.data.color {
-                        let to_u8 = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
-                        color = Color32::from_rgba_premultiplied(
-                            to_u8(r),
-                            to_u8(g),
-                            to_u8(b),
-                            to_u8(a),
-                        );
+                &DragKind::Rotate {
+                    center,
+                    start_deg,
+                    press_angle,
+                } => {
+                    // screen angles grow clockwise, yaw counter-clockwise (seen from +Y)
+                    let angle = (world - center).angle();
+                    let mut deg = start_deg - (angle - press_angle).to_degrees();
+                    if snap.is_some() {
+                        deg = snap_to(deg, ROTATE_SNAP_DEG);
+                    }
+                    if let Some(data) = transform(scene, d.entity) {
+                        data.rot_y_deg = Some((deg + 180.0).rem_euclid(360.0) - 180.0);
                     }
                 }
-                "Mesh3d" => {
-                    if let Some(s) = comp.data.shape.as_deref() {
-                        shape = Some(s);
-                        match s {
-                            "Circle" => radius = comp.data.radius.or(Some(1.0)),
-                            "Cuboid" => {
-                                let x = comp.data.x.unwrap_or(1.0);
-                                let z = comp.data.z.unwrap_or(1.0);
-                                cuboid_xz = Some((x, z));
+                &DragKind::Scale {
+                    axis,
+                    center,
+                    yaw,
+                    start,
+                    press_dist,
+                } => {
+                    if press_dist.abs() > f32::EPSILON
+                        && let Some(data) = transform(scene, d.entity)
+                    {
+                        let f = axis.measure(world - center, yaw) / press_dist;
+                        let clamp = |v: f32| {
+                            if v.abs() < 0.01 {
+                                0.01f32.copysign(v)
+                            } else {
+                                v
                             }
-                            _ => {}
-                        }
+                        };
+                        data.scale = Some(match axis {
+                            ScaleAxis::X => (clamp(start.0 * f), start.1, start.2),
+                            ScaleAxis::Z => (start.0, start.1, clamp(start.2 * f)),
+                            ScaleAxis::Uniform => {
+                                (clamp(start.0 * f), clamp(start.1 * f), clamp(start.2 * f))
+                            }
+                        });
                     }
                 }
-                _ => {}
             }
-        }
-
-        match shape {
-            Some("Circle") => {
-                let r = radius.unwrap_or(1.0);
-                cmds.push(DrawCmd {
-                    kind: DrawKind::Circle,
-                    pos: egui::vec2(pos_xz.0, pos_xz.1),
-                    size: egui::vec2(r, r),
-                    color,
-                    height_y: pos_y,
-                });
+            if response.drag_delta() != egui::Vec2::ZERO {
+                edit = ViewportEdit::Live;
             }
-            Some("Cuboid") => {
-                let (x, z) = cuboid_xz.unwrap_or((1.0, 1.0));
-                cmds.push(DrawCmd {
-                    kind: DrawKind::Rect,
-                    pos: egui::vec2(pos_xz.0, pos_xz.1),
-                    size: egui::vec2(x, z),
-                    color,
-                    height_y: pos_y,
-                });
+            // redraw with the new transform this frame
+            cmds = gather_draw_cmds(scene, plane);
+            depth_cue.apply(&mut cmds, ui.visuals().extreme_bg_color);
+            cmds.sort_by(|a, b| a.depth.partial_cmp(&b.depth).unwrap_or(Ordering::Equal));
+        }
+        if !response.dragged() {
+            let d = drag.take().expect("checked above");
+            let transforms = |s: &SceneDoc| {
+                s.entities
+                    .iter()
+                    .map(|e| e.component("Transform").cloned())
+                    .collect::<Vec<_>>()
+            };
+            if transforms(scene) != transforms(&d.before) {
+                edit = ViewportEdit::Finished(d.before);
             }
-            _ => {}
         }
-    }
-
-    cmds
-}
-
-fn draw_scene_preview(
-    ui: &mut egui::Ui,
-    scene: &crate::project::SceneDoc,
-    view_offset: &mut egui::Vec2,
-    view_zoom: &mut f32,
-) {
-    use std::cmp::Ordering;
-
-    // Panel area
-    let avail = ui.available_size();
-    let (response, painter) = ui.allocate_painter(avail, egui::Sense::click_and_drag());
-
-    // Mouse wheel zoom:
-    if response.hovered() {
-        if let Some(scroll) = ui.input(|i| i.smooth_scroll_delta.y).into() {
-            // zoom around mouse
-            let zoom_factor = (1.0 + scroll * -0.001).clamp(0.5, 4.0);
-            let old_zoom = *view_zoom;
-            let new_zoom = (old_zoom * zoom_factor).clamp(10.0, 400.0);
-
-            // keep world point under cursor stable
-            let mouse_pos = ui.input(|i| i.pointer.hover_pos());
-            if let Some(mp) = mouse_pos {
-                let world_before = screen_to_world(mp, response.rect, *view_offset, old_zoom);
-                *view_zoom = new_zoom;
-                let world_after = screen_to_world(mp, response.rect, *view_offset, new_zoom);
-                *view_offset += world_after - world_before;
-            } else {
-                *view_zoom = new_zoom;
+    } else if measuring {
+        if let Some((start, end)) = ruler
+            && let Some(pos) = response.interact_pointer_pos()
+        {
+            let mut d = to_world(pos) - *start;
+            if let Some(step) = snap {
+                d = egui::vec2(snap_to(d.x, step), snap_to(d.y, step));
+            }
+            // Shift keeps the line along x or z, whichever dominates
+            if ui.input(|i| i.modifiers.shift) {
+                if d.x.abs() >= d.y.abs() {
+                    d.y = 0.0;
+                } else {
+                    d.x = 0.0;
+                }
             }
+            *end = *start + d;
         }
-    }
-
-    // Drag to pan:
-    if response.dragged() {
+    } else if response.dragged() {
+        // Drag on empty space pans
         let drag = response.drag_delta();
         // convert screen drag to world delta
         *view_offset -= drag / *view_zoom;
@@ -873,54 +4970,532 @@ fn draw_scene_preview(
     // Background
     painter.rect_filled(response.rect, 0.0, ui.visuals().extreme_bg_color);
 
-    // Draw grid (every 1.0 world unit)
+    // Draw grid (at the snap step while snapping, else the project's spacing)
     draw_grid(
         &painter,
         response.rect,
         *view_offset,
         *view_zoom,
-        ui.visuals().weak_text_color(),
+        snap.unwrap_or(config.grid_spacing),
+        ui.visuals(),
     );
-
-    // Gather draw commands from scene
-    let mut cmds = gather_draw_cmds(scene);
-
-    // 🔹 Depth sort: lower Y first, higher Y last (so higher objects draw on top)
-    cmds.sort_by(|a, b| {
-        a.height_y
-            .partial_cmp(&b.height_y)
-            .unwrap_or(Ordering::Equal)
-    });
+    if let Some(bounds) = &config.world_bounds {
+        draw_world_bounds(
+            &painter,
+            response.rect,
+            *view_offset,
+            *view_zoom,
+            bounds,
+            plane,
+            ui.visuals(),
+        );
+    }
 
     // Draw each
-    for cmd in cmds {
+    for cmd in &cmds {
         match cmd.kind {
             DrawKind::Circle => {
-                let center = world_to_screen(cmd.pos, response.rect, *view_offset, *view_zoom);
-                let r_px = cmd.size.x * *view_zoom;
-                painter.circle_filled(center, r_px, cmd.color);
-                painter.circle_stroke(
-                    center,
-                    r_px,
-                    egui::Stroke::new(1.0, ui.visuals().widgets.noninteractive.fg_stroke.color),
-                );
+                // an ellipse once scaled unevenly, turned by yaw
+                let stroke =
+                    egui::Stroke::new(1.0, ui.visuals().widgets.noninteractive.fg_stroke.color);
+                let points = (0..48)
+                    .map(|i| {
+                        let a = i as f32 / 48.0 * std::f32::consts::TAU;
+                        let local = egui::vec2(a.cos() * cmd.size.x, a.sin() * cmd.size.y);
+                        let world = cmd.pos + rotate_xz(local, cmd.yaw);
+                        world_to_screen(world, response.rect, *view_offset, *view_zoom)
+                    })
+                    .collect();
+                painter.add(egui::Shape::convex_polygon(points, cmd.color, stroke));
             }
             DrawKind::Rect => {
-                // Rect centered at pos with size.x by size.y (world)
+                // Rect centered at pos with size.x by size.y (world), turned by yaw
                 let half = cmd.size * 0.5;
-                let p0 = world_to_screen(cmd.pos - half, response.rect, *view_offset, *view_zoom);
-                let p1 = world_to_screen(cmd.pos + half, response.rect, *view_offset, *view_zoom);
-                let rect = egui::Rect::from_two_pos(p0, p1);
-                painter.rect_filled(rect, 2.0, cmd.color);
-                painter.rect_stroke(
-                    rect,
-                    2.0,
-                    egui::Stroke::new(1.0, ui.visuals().widgets.noninteractive.fg_stroke.color),
-                    egui::StrokeKind::Inside,
-                );
+                let stroke =
+                    egui::Stroke::new(1.0, ui.visuals().widgets.noninteractive.fg_stroke.color);
+                let points = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+                    .into_iter()
+                    .map(|(sx, sz)| {
+                        let local = egui::vec2(half.x * sx, half.y * sz);
+                        let world = cmd.pos + rotate_xz(local, cmd.yaw);
+                        world_to_screen(world, response.rect, *view_offset, *view_zoom)
+                    })
+                    .collect();
+                painter.add(egui::Shape::convex_polygon(points, cmd.color, stroke));
+                if let Some(label) = &cmd.label {
+                    painter.text(
+                        world_to_screen(cmd.pos, response.rect, *view_offset, *view_zoom),
+                        egui::Align2::CENTER_CENTER,
+                        label,
+                        egui::FontId::proportional(11.0),
+                        ui.visuals().strong_text_color(),
+                    );
+                }
+            }
+            DrawKind::Light => {
+                // constant screen size so lights stay visible at any zoom
+                let center = world_to_screen(cmd.pos, response.rect, *view_offset, *view_zoom);
+                let outline = ui.visuals().widgets.noninteractive.fg_stroke.color;
+                painter.circle_filled(center, 5.0, cmd.color);
+                painter.circle_stroke(center, 5.0, egui::Stroke::new(1.0, outline));
+                painter.circle_stroke(center, 9.0, egui::Stroke::new(1.5, cmd.color));
+            }
+            DrawKind::Camera { dir, fov_deg } => {
+                // wedge from the camera along its view direction, constant screen size
+                let apex = world_to_screen(cmd.pos, response.rect, *view_offset, *view_zoom);
+                let half = (fov_deg * 0.5).to_radians();
+                let edge = |a: f32| {
+                    let (sin, cos) = a.sin_cos();
+                    let v = egui::vec2(dir.x * cos - dir.y * sin, dir.x * sin + dir.y * cos);
+                    apex + v * CAMERA_WEDGE_PX
+                };
+                let outline = ui.visuals().widgets.noninteractive.fg_stroke.color;
+                painter.add(egui::Shape::convex_polygon(
+                    vec![apex, edge(-half), edge(half)],
+                    cmd.color.gamma_multiply(0.35),
+                    egui::Stroke::new(1.0, outline),
+                ));
+                painter.circle_filled(apex, 3.0, outline);
+            }
+        }
+    }
+
+    // Outline everything selected
+    let outline = egui::Stroke::new(1.5, ui.visuals().selection.stroke.color);
+    for cmd in cmds
+        .iter()
+        .filter(|c| *selected == Some(c.entity) || extra.contains(&c.entity))
+    {
+        let b = draw_cmd_bounds(cmd);
+        let min = world_to_screen(b.min.to_vec2(), response.rect, *view_offset, *view_zoom);
+        let max = world_to_screen(b.max.to_vec2(), response.rect, *view_offset, *view_zoom);
+        painter.rect_stroke(
+            egui::Rect::from_two_pos(min, max).expand(3.0),
+            2.0,
+            outline,
+            egui::StrokeKind::Outside,
+        );
+    }
+
+    // Gizmo for the selection
+    if let Some((_, g)) = gizmo_at(scene, &cmds, *selected, *view_offset, *view_zoom) {
+        let accent = egui::Stroke::new(2.0, ui.visuals().selection.stroke.color);
+        match gizmo {
+            GizmoMode::Move | GizmoMode::Measure => {}
+            GizmoMode::Rotate => {
+                painter.circle_stroke(g.center, g.ring_r, accent);
+                // tick at the entity's local +x
+                let dir = rotate_xz(egui::vec2(1.0, 0.0), g.yaw);
+                painter.line_segment([g.center, g.center + dir * g.ring_r], accent);
+            }
+            GizmoMode::Scale => {
+                for h in [g.handle_x, g.handle_z, g.handle_xz] {
+                    painter.line_segment([g.center, h], accent);
+                    painter.rect_filled(
+                        egui::Rect::from_center_size(h, egui::vec2(8.0, 8.0)),
+                        1.0,
+                        accent.color,
+                    );
+                }
+            }
+        }
+    }
+
+    // Pointer readout and a crosshair at the nearest grid intersection
+    if let Some(hover) = response.hover_pos() {
+        let step = snap.unwrap_or(config.grid_spacing);
+        let world = screen_to_world(hover, response.rect, *view_offset, *view_zoom);
+        let grid = world_to_screen(
+            egui::vec2(snap_to(world.x, step), snap_to(world.y, step)),
+            response.rect,
+            *view_offset,
+            *view_zoom,
+        );
+        let faint = egui::Stroke::new(1.0, ui.visuals().text_color().gamma_multiply(0.35));
+        painter.line_segment(
+            [grid - egui::vec2(8.0, 0.0), grid + egui::vec2(8.0, 0.0)],
+            faint,
+        );
+        painter.line_segment(
+            [grid - egui::vec2(0.0, 8.0), grid + egui::vec2(0.0, 8.0)],
+            faint,
+        );
+        let (axis, sign) = plane.vertical_axis();
+        painter.text(
+            response.rect.right_bottom() + egui::vec2(-6.0, -4.0),
+            egui::Align2::RIGHT_BOTTOM,
+            format!("x {:.2}  {axis} {:.2}", world.x, world.y * sign),
+            egui::FontId::monospace(12.0),
+            ui.visuals().weak_text_color(),
+        );
+    }
+
+    // Measurement, kept until cleared or replaced by the next ruler drag
+    if let Some((start, end)) = *ruler {
+        let stroke = egui::Stroke::new(2.0, ui.visuals().warn_fg_color);
+        let a = world_to_screen(start, response.rect, *view_offset, *view_zoom);
+        let b = world_to_screen(end, response.rect, *view_offset, *view_zoom);
+        painter.line_segment([a, b], stroke);
+        painter.circle_filled(a, 3.0, stroke.color);
+        painter.circle_filled(b, 3.0, stroke.color);
+        let d = end - start;
+        let (axis, sign) = plane.vertical_axis();
+        painter.text(
+            a.lerp(b, 0.5) + egui::vec2(0.0, -6.0),
+            egui::Align2::CENTER_BOTTOM,
+            format!(
+                "{:.2} u  (dx {:.2}, d{axis} {:.2})",
+                d.length(),
+                d.x,
+                d.y * sign
+            ),
+            egui::FontId::monospace(12.0),
+            ui.visuals().strong_text_color(),
+        );
+    }
+
+    if show_ids {
+        let to_screen = |w| world_to_screen(w, response.rect, *view_offset, *view_zoom);
+        draw_id_labels(
+            &painter,
+            scene,
+            &cmds,
+            plane,
+            *view_zoom,
+            to_screen,
+            ui.visuals(),
+        );
+    }
+
+    draw_axis_gizmo(&painter, response.rect, plane, ui.visuals());
+
+    if let Some(map) = &minimap {
+        map.draw(
+            &painter,
+            &cmds,
+            response.rect,
+            *view_offset,
+            *view_zoom,
+            ui.visuals(),
+        );
+    }
+
+    edit
+}
+
+/// Each entity's id over its shape (topmost shape first), sized with the
+/// zoom within readable bounds. A label that would overlap one already
+/// placed is skipped. Hidden entities get a dimmed label at their position.
+fn draw_id_labels(
+    painter: &egui::Painter,
+    scene: &SceneDoc,
+    cmds: &[DrawCmd],
+    plane: ViewPlane,
+    zoom: f32,
+    to_screen: impl Fn(egui::Vec2) -> egui::Pos2,
+    visuals: &egui::Visuals,
+) {
+    let font = egui::FontId::proportional((zoom * 0.3).clamp(9.0, 14.0));
+    let mut labelled = BTreeSet::new();
+    let mut placed: Vec<egui::Rect> = Vec::new();
+    let mut place = |pos: egui::Pos2, id: &str, color: egui::Color32| {
+        let galley = painter.layout_no_wrap(id.to_string(), font.clone(), color);
+        let rect = egui::Rect::from_center_size(pos, galley.size()).expand(1.0);
+        if placed.iter().any(|r| r.intersects(rect)) {
+            return;
+        }
+        painter.rect_filled(rect, 2.0, visuals.extreme_bg_color.gamma_multiply(0.7));
+        painter.galley(rect.min + egui::vec2(1.0, 1.0), galley, color);
+        placed.push(rect);
+    };
+
+    for cmd in cmds.iter().rev() {
+        if !labelled.insert(cmd.entity) {
+            continue;
+        }
+        let mut pos = to_screen(cmd.pos);
+        if cmd.label.is_some() {
+            pos.y += font.size + 2.0; // under the asset name
+        }
+        place(
+            pos,
+            &scene.entities[cmd.entity].id,
+            visuals.strong_text_color(),
+        );
+    }
+
+    let dim = visuals.weak_text_color().gamma_multiply(0.6);
+    for (i, ent) in scene.entities.iter().enumerate() {
+        if scene.hidden_in_tree(i)
+            && ent.component("Transform").is_some()
+            && let Some(t) = scene.world_translation(i)
+        {
+            place(to_screen(plane.project(t).0), &ent.id, dim);
+        }
+    }
+}
+
+/// Which world axes run along screen x and y, in the bottom-left corner.
+fn draw_axis_gizmo(
+    painter: &egui::Painter,
+    rect: egui::Rect,
+    plane: ViewPlane,
+    visuals: &egui::Visuals,
+) {
+    const LEN: f32 = 22.0;
+    let origin = rect.left_bottom() + egui::vec2(18.0, -40.0);
+    let (axis, sign) = plane.vertical_axis();
+    let (color, label) = match plane {
+        ViewPlane::Top => (egui::Color32::from_rgb(70, 120, 230), "Top"),
+        ViewPlane::Front => (egui::Color32::from_rgb(80, 180, 80), "Front"),
+    };
+    let x_color = egui::Color32::from_rgb(220, 70, 70);
+    let font = egui::FontId::monospace(11.0);
+    painter.arrow(
+        origin,
+        egui::vec2(LEN, 0.0),
+        egui::Stroke::new(2.0, x_color),
+    );
+    painter.text(
+        origin + egui::vec2(LEN + 4.0, 0.0),
+        egui::Align2::LEFT_CENTER,
+        "+x",
+        font.clone(),
+        x_color,
+    );
+    // the arrow shows the axis's positive direction on screen
+    let up = egui::vec2(0.0, LEN * sign);
+    painter.arrow(origin, up, egui::Stroke::new(2.0, color));
+    painter.text(
+        origin + up + egui::vec2(0.0, 6.0 * sign),
+        egui::Align2::CENTER_CENTER,
+        format!("+{axis}"),
+        font.clone(),
+        color,
+    );
+    painter.text(
+        origin + egui::vec2(0.0, 26.0),
+        egui::Align2::LEFT_CENTER,
+        label,
+        font,
+        visuals.weak_text_color(),
+    );
+}
+
+/// Side of the minimap inset, px.
+const MINIMAP_PX: f32 = 160.0;
+
+/// Fit-to-content overview drawn in the viewport's top-right corner.
+struct Minimap {
+    rect: egui::Rect,   // screen rect of the inset
+    offset: egui::Vec2, // world xz at its center
+    zoom: f32,          // inset pixels per world unit
+}
+
+impl Minimap {
+    /// Inset framing every draw command, or None for an empty scene or a
+    /// viewport too small to hold it.
+    fn fit(cmds: &[DrawCmd], view: egui::Rect) -> Option<Self> {
+        const MARGIN: f32 = 8.0;
+        const PAD: f32 = 10.0; // keeps shapes off the inset's border
+        if view.width() < MINIMAP_PX * 2.0 || view.height() < MINIMAP_PX * 2.0 {
+            return None;
+        }
+        let bounds = cmds.iter().map(draw_cmd_bounds).reduce(|a, b| a.union(b))?;
+        let rect = egui::Rect::from_min_size(
+            view.right_top() + egui::vec2(-MARGIN - MINIMAP_PX, MARGIN),
+            egui::vec2(MINIMAP_PX, MINIMAP_PX),
+        );
+        let size = bounds.size().max(egui::vec2(1.0, 1.0));
+        let zoom = ((MINIMAP_PX - 2.0 * PAD) / size.x).min((MINIMAP_PX - 2.0 * PAD) / size.y);
+        Some(Self {
+            rect,
+            offset: bounds.center().to_vec2(),
+            zoom,
+        })
+    }
+
+    fn to_world(&self, pos: egui::Pos2) -> egui::Vec2 {
+        screen_to_world(pos, self.rect, self.offset, self.zoom)
+    }
+
+    fn to_screen(&self, world: egui::Vec2) -> egui::Pos2 {
+        world_to_screen(world, self.rect, self.offset, self.zoom)
+    }
+
+    /// Footprints of `cmds` plus the main view's extent (`view` at
+    /// `view_offset`/`view_zoom`) as an outline.
+    fn draw(
+        &self,
+        painter: &egui::Painter,
+        cmds: &[DrawCmd],
+        view: egui::Rect,
+        view_offset: egui::Vec2,
+        view_zoom: f32,
+        visuals: &egui::Visuals,
+    ) {
+        let border = visuals.widgets.noninteractive.bg_stroke;
+        painter.rect_filled(self.rect, 2.0, visuals.window_fill.gamma_multiply(0.9));
+        let clipped = painter.with_clip_rect(self.rect);
+        for cmd in cmds {
+            match cmd.kind {
+                DrawKind::Circle | DrawKind::Rect => {
+                    let b = draw_cmd_bounds(cmd);
+                    let r = egui::Rect::from_two_pos(
+                        self.to_screen(b.min.to_vec2()),
+                        self.to_screen(b.max.to_vec2()),
+                    );
+                    // keep tiny shapes visible
+                    let r = egui::Rect::from_center_size(
+                        r.center(),
+                        r.size().max(egui::vec2(2.0, 2.0)),
+                    );
+                    clipped.rect_filled(r, 0.0, cmd.color);
+                }
+                DrawKind::Light | DrawKind::Camera { .. } => {
+                    clipped.circle_filled(self.to_screen(cmd.pos), 2.0, cmd.color);
+                }
             }
         }
+        let seen = egui::Rect::from_two_pos(
+            self.to_screen(screen_to_world(view.min, view, view_offset, view_zoom)),
+            self.to_screen(screen_to_world(view.max, view, view_offset, view_zoom)),
+        );
+        clipped.rect_stroke(
+            seen,
+            0.0,
+            egui::Stroke::new(1.5, visuals.selection.stroke.color),
+            egui::StrokeKind::Middle,
+        );
+        painter.rect_stroke(self.rect, 2.0, border, egui::StrokeKind::Inside);
+    }
+}
+
+/// Manipulation applied by dragging in the viewport (W/E/R, M for the ruler).
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+enum GizmoMode {
+    #[default]
+    Move,
+    Rotate,
+    Scale,
+    Measure, // drag measures distances; doesn't touch the scene
+}
+
+#[derive(Clone, Copy)]
+enum ScaleAxis {
+    X,
+    Z,
+    Uniform,
+}
+
+impl ScaleAxis {
+    /// Distance of world offset `v` from the gizmo center along this axis
+    /// (plain length for uniform scaling).
+    fn measure(self, v: egui::Vec2, yaw: f32) -> f32 {
+        match self {
+            ScaleAxis::X => v.dot(rotate_xz(egui::vec2(1.0, 0.0), yaw)),
+            ScaleAxis::Z => v.dot(rotate_xz(egui::vec2(0.0, 1.0), yaw)),
+            ScaleAxis::Uniform => v.length(),
+        }
+    }
+}
+
+/// Rotation steps while snapping is on.
+const ROTATE_SNAP_DEG: f32 = 15.0;
+/// How close (px) a press must be to a gizmo handle or ring to grab it.
+const GIZMO_GRAB_PX: f32 = 8.0;
+
+/// Screen-space layout of the selected entity's gizmo.
+struct GizmoGeom {
+    center: egui::Pos2,
+    center_world: egui::Vec2,
+    yaw: f32,       // world yaw, parents included
+    rot_y_deg: f32, // the entity's own yaw, which a rotate drag edits
+    scale: (f32, f32, f32),
+    ring_r: f32,
+    handle_x: egui::Pos2,
+    handle_z: egui::Pos2,
+    handle_xz: egui::Pos2,
+}
+
+/// Gizmo layout for entity `sel`, or None if it has no Transform or is
+/// hidden or locked.
+fn gizmo_geom(
+    scene: &SceneDoc,
+    cmds: &[DrawCmd],
+    sel: usize,
+    rect: egui::Rect,
+    offset_world: egui::Vec2,
+    zoom: f32,
+) -> Option<GizmoGeom> {
+    let ent = scene.entities.get(sel)?;
+    if scene.hidden_in_tree(sel) || ent.is_locked() {
+        return None;
     }
+    let data = &ent.component("Transform")?.data;
+    let (x, _, z) = scene.world_translation(sel)?;
+    let center_world = egui::vec2(x, z);
+    let rot_y_deg = data.rot_y_deg.unwrap_or(0.0);
+    let yaw = scene.parent_yaw_scale(sel).0 + rot_y_deg.to_radians();
+
+    // local half extents of the shape (scale applied), or a unit box
+    let half = cmds
+        .iter()
+        .find(|c| c.entity == sel)
+        .and_then(|c| match c.kind {
+            DrawKind::Circle => Some(c.size),
+            DrawKind::Rect => Some(c.size * 0.5),
+            _ => None,
+        })
+        .unwrap_or(egui::vec2(0.5, 0.5));
+
+    const PAD_PX: f32 = 14.0;
+    let center = world_to_screen(center_world, rect, offset_world, zoom);
+    let ux = rotate_xz(egui::vec2(1.0, 0.0), yaw);
+    let uz = rotate_xz(egui::vec2(0.0, 1.0), yaw);
+    let corner = ux * half.x + uz * half.y;
+    Some(GizmoGeom {
+        center,
+        center_world,
+        yaw,
+        rot_y_deg,
+        scale: data.scale.unwrap_or((1.0, 1.0, 1.0)),
+        ring_r: half.length() * zoom + PAD_PX,
+        handle_x: center + ux * (half.x * zoom + PAD_PX),
+        handle_z: center + uz * (half.y * zoom + PAD_PX),
+        handle_xz: center + corner * zoom + corner.normalized() * PAD_PX,
+    })
+}
+
+/// Length of the camera wedge in screen pixels.
+const CAMERA_WEDGE_PX: f32 = 28.0;
+
+/// Entity of the topmost draw command under screen position `pos`.
+/// `cmds` must be in draw order.
+fn hit_test(
+    cmds: &[DrawCmd],
+    pos: egui::Pos2,
+    rect: egui::Rect,
+    offset_world: egui::Vec2,
+    zoom: f32,
+) -> Option<usize> {
+    let world = screen_to_world(pos, rect, offset_world, zoom);
+    cmds.iter()
+        .rev()
+        .find(|cmd| {
+            let center = world_to_screen(cmd.pos, rect, offset_world, zoom);
+            // pointer in the shape's own (unrotated) frame
+            let local = rotate_xz(world - cmd.pos, -cmd.yaw);
+            match cmd.kind {
+                DrawKind::Circle => (local / cmd.size).length() <= 1.0,
+                DrawKind::Rect => {
+                    local.x.abs() <= cmd.size.x * 0.5 && local.y.abs() <= cmd.size.y * 0.5
+                }
+                DrawKind::Light => center.distance(pos) <= 9.0, // the marker ring
+                DrawKind::Camera { .. } => center.distance(pos) <= CAMERA_WEDGE_PX,
+            }
+        })
+        .map(|cmd| cmd.entity)
 }
 
 fn world_to_screen(
@@ -946,51 +5521,182 @@ fn screen_to_world(
     v + offset_world
 }
 
-fn draw_grid(
+/// Outline of the project's world bounds: the rectangle from the top, its x
+/// extent as two verticals from the front.
+fn draw_world_bounds(
     painter: &egui::Painter,
     rect: egui::Rect,
     offset_world: egui::Vec2,
     zoom: f32,
-    color: egui::Color32,
+    bounds: &WorldBounds,
+    plane: ViewPlane,
+    visuals: &egui::Visuals,
 ) {
-    // grid every 1 world unit; show about ~50 lines max
-    let spacing_px = zoom;
-    if spacing_px < 8.0 {
-        return; // too dense, skip
+    let stroke = egui::Stroke::new(1.5, visuals.warn_fg_color.linear_multiply(0.7));
+    let to_screen = |x: f32, z: f32| world_to_screen(egui::vec2(x, z), rect, offset_world, zoom);
+    match plane {
+        ViewPlane::Top => {
+            let r = egui::Rect::from_two_pos(
+                to_screen(bounds.min.0, bounds.min.1),
+                to_screen(bounds.max.0, bounds.max.1),
+            );
+            painter.rect_stroke(r, 0.0, stroke, egui::StrokeKind::Middle);
+        }
+        ViewPlane::Front => {
+            for x in [bounds.min.0, bounds.max.0] {
+                let sx = to_screen(x, 0.0).x;
+                painter.line_segment(
+                    [egui::pos2(sx, rect.top()), egui::pos2(sx, rect.bottom())],
+                    stroke,
+                );
+            }
+        }
     }
+}
 
-    let center = rect.center();
-    // how many lines to each side
-    let half_w = rect.width() * 0.5 / spacing_px;
-    let half_h = rect.height() * 0.5 / spacing_px;
-
-    let ox = offset_world.x.fract(); // fractional part to align grid smoothly
-    let oz = offset_world.y.fract();
+/// Grid lines every `step` world units (coarsened while too dense to read);
+/// every 10 units, the world axes included, is drawn bold.
+fn draw_grid(
+    painter: &egui::Painter,
+    rect: egui::Rect,
+    offset_world: egui::Vec2,
+    zoom: f32,
+    step: f32,
+    visuals: &egui::Visuals,
+) {
+    let mut step = step.max(0.01);
+    while step * zoom < 8.0 {
+        step *= 2.0;
+    }
 
-    let x0_idx = (-half_w.floor() as i32) - 2;
-    let x1_idx = (half_w.ceil() as i32) + 2;
-    let z0_idx = (-half_h.floor() as i32) - 2;
-    let z1_idx = (half_h.ceil() as i32) + 2;
+    let min = screen_to_world(rect.left_top(), rect, offset_world, zoom);
+    let max = screen_to_world(rect.right_bottom(), rect, offset_world, zoom);
 
-    let thin = egui::Stroke::new(1.0, color.linear_multiply(0.25));
-    let bold = egui::Stroke::new(1.5, color.linear_multiply(0.6));
+    // faint lines vanish against the light theme's near-white background
+    let color = visuals.weak_text_color();
+    let (thin_a, bold_a) = if visuals.dark_mode {
+        (0.25, 0.6)
+    } else {
+        (0.4, 0.85)
+    };
+    let thin = egui::Stroke::new(1.0, color.linear_multiply(thin_a));
+    let bold = egui::Stroke::new(1.5, color.linear_multiply(bold_a));
+    // bold every 10 units, or every 10 lines once the lines are that far apart
+    let major = if step < 10.0 { 10.0 } else { step * 10.0 };
+    let stroke_at = |v: f32| {
+        let q = v / major;
+        if (q - q.round()).abs() < 1e-3 {
+            bold
+        } else {
+            thin
+        }
+    };
 
-    for ix in x0_idx..=x1_idx {
-        let x = (ix as f32 - ox) * spacing_px;
-        let sx = center.x + x;
-        let stroke = if ix == 0 { bold } else { thin };
+    for ix in (min.x / step).floor() as i32..=(max.x / step).ceil() as i32 {
+        let sx = world_to_screen(egui::vec2(ix as f32 * step, 0.0), rect, offset_world, zoom).x;
+        let stroke = stroke_at(ix as f32 * step);
         painter.line_segment(
             [egui::pos2(sx, rect.top()), egui::pos2(sx, rect.bottom())],
             stroke,
         );
     }
-    for iz in z0_idx..=z1_idx {
-        let y = (iz as f32 - oz) * spacing_px;
-        let sy = center.y + y;
-        let stroke = if iz == 0 { bold } else { thin };
+    for iz in (min.y / step).floor() as i32..=(max.y / step).ceil() as i32 {
+        let sy = world_to_screen(egui::vec2(0.0, iz as f32 * step), rect, offset_world, zoom).y;
+        let stroke = stroke_at(iz as f32 * step);
         painter.line_segment(
             [egui::pos2(rect.left(), sy), egui::pos2(rect.right(), sy)],
             stroke,
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scene(ron: &str) -> SceneDoc {
+        crate::project::parse_scene(ron).expect("test scene parses")
+    }
+
+    #[test]
+    fn attach_fills_params_from_schema_defaults() {
+        let schema: Schema = ron::from_str(
+            r#"(scripts: [(
+                name: "Patrol",
+                rust_symbol: "crate::scripts::patrol::Patrol",
+                params: [
+                    (key: "speed", label: "Speed", ty: F64, default: Some("2.5")),
+                    (key: "waypoint", label: "Waypoint", ty: Vec3, default: Some("(1, 0, -3)")),
+                    (key: "tag", label: "Tag", ty: String, default: Some("\"guard\"")),
+                    (key: "laps", label: "Laps", ty: I64, default: Some("many")),
+                    (key: "loop", label: "Loop", ty: Bool, default: None),
+                ],
+            )])"#,
+        )
+        .expect("test schema parses");
+
+        let attached = schema.scripts[0].attach();
+        assert_eq!(attached.name, "Patrol");
+        let expected: BTreeMap<String, ron::Value> = [
+            ("speed", ron::Value::from(2.5f64)),
+            ("waypoint", vec![1.0f64, 0.0, -3.0].into()),
+            ("tag", "guard".into()),
+            ("laps", 0i64.into()), // unparsable: the type's zero
+            ("loop", false.into()),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect();
+        assert_eq!(attached.params, expected);
+    }
+
+    #[test]
+    fn children_of_hidden_entities_are_not_drawn() {
+        let s = scene(
+            r#"(entities: [
+                (id: "group", hidden: true, components: []),
+                (id: "crate", parent: Some("group"), components: [
+                    (type_id: "Mesh3d", data: (shape: Some("Cuboid"))),
+                ]),
+                (id: "floor", components: [
+                    (type_id: "Mesh3d", data: (shape: Some("Cuboid"))),
+                ]),
+            ])"#,
+        );
+        let drawn: Vec<usize> = gather_draw_cmds(&s, ViewPlane::Top)
+            .iter()
+            .map(|c| c.entity)
+            .collect();
+        assert_eq!(drawn, [2]);
+    }
+
+    #[test]
+    fn degenerate_mesh_sizes_are_clamped() {
+        let s = scene(
+            r#"(entities: [
+                (id: "flat", components: [
+                    (type_id: "Mesh3d", data: (shape: Some("Circle"), radius: Some(0.0))),
+                ]),
+                (id: "inside_out", components: [
+                    (type_id: "Mesh3d", data: (shape: Some("Circle"), radius: Some(-2.0))),
+                ]),
+                (id: "box", components: [
+                    (type_id: "Mesh3d", data: (shape: Some("Cuboid"),
+                        x: Some(0.0), y: Some(-1.0), z: Some(-0.5))),
+                ]),
+            ])"#,
+        );
+        for plane in [ViewPlane::Top, ViewPlane::Front] {
+            let cmds = gather_draw_cmds(&s, plane);
+            assert_eq!(cmds.len(), 3);
+            for c in &cmds {
+                assert!(
+                    c.size.x >= MIN_MESH_DIM && c.size.y >= MIN_MESH_DIM,
+                    "{} drawn at {:?}",
+                    s.entities[c.entity].id,
+                    c.size
+                );
+            }
+        }
+    }
+}
@@ -1,85 +1,203 @@
 use crate::project::Diagnostic;
-use crossbeam::channel::{Receiver, Sender, unbounded};
-use serde::Deserialize;
-use std::io::BufRead;
-use std::path::PathBuf;
+use crossbeam::channel::{Receiver, Sender, select, unbounded};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io::{self, BufRead, Read, Write};
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use std::thread;
 use std::time::Instant;
 
 pub enum BuildJob {
-    Check { root: PathBuf },
+    Check {
+        root: PathBuf,
+        release: bool,
+    },
+    /// `cargo clippy`; its lints arrive as ordinary compiler messages.
+    Clippy {
+        root: PathBuf,
+        release: bool,
+    },
+    Build {
+        root: PathBuf,
+        release: bool,
+    },
+}
+
+impl BuildJob {
+    /// The diagnostics pass: clippy when `clippy` is set, else check.
+    pub fn lint(root: PathBuf, release: bool, clippy: bool) -> Self {
+        if clippy {
+            BuildJob::Clippy { root, release }
+        } else {
+            BuildJob::Check { root, release }
+        }
+    }
+
+    fn parts(&self) -> (&'static str, &PathBuf, bool) {
+        match self {
+            BuildJob::Check { root, release } => ("check", root, *release),
+            BuildJob::Clippy { root, release } => ("clippy", root, *release),
+            BuildJob::Build { root, release } => ("build", root, *release),
+        }
+    }
+
+    /// Whether this job makes `older` pointless: a newer lint pass (check
+    /// or clippy) replaces an older one for the same project. Builds are
+    /// never superseded and never supersede anything.
+    fn supersedes(&self, older: &BuildJob) -> bool {
+        let lint = |job: &BuildJob| !matches!(job, BuildJob::Build { .. });
+        lint(self) && lint(older) && self.parts().1 == older.parts().1
+    }
+
+    /// Command line as shown in status messages, e.g. `cargo build --release`.
+    pub fn describe(&self) -> String {
+        let (subcommand, _, release) = self.parts();
+        if release {
+            format!("cargo {subcommand} --release")
+        } else {
+            format!("cargo {subcommand}")
+        }
+    }
 }
 
 pub enum BuildResult {
+    /// Sent right before cargo is spawned for a job.
+    Started { root: PathBuf, command: String },
+    /// cargo succeeded; `diagnostics` holds its warnings, if any.
     Ok {
+        command: String,
         duration_ms: u128,
+        diagnostics: Vec<Diagnostic>,
     },
+    /// cargo failed or reported errors.
     Err {
+        command: String,
         duration_ms: u128,
         diagnostics: Vec<Diagnostic>,
     },
 }
 
+impl BuildResult {
+    /// The finished job as a `BuildReport`; None for `Started`.
+    pub fn report(&self) -> Option<BuildReport<'_>> {
+        match self {
+            BuildResult::Started { .. } => None,
+            BuildResult::Ok {
+                command,
+                duration_ms,
+                diagnostics,
+            } => Some(BuildReport {
+                ok: true,
+                command,
+                duration_ms: *duration_ms,
+                diagnostics,
+            }),
+            BuildResult::Err {
+                command,
+                duration_ms,
+                diagnostics,
+            } => Some(BuildReport {
+                ok: false,
+                command,
+                duration_ms: *duration_ms,
+                diagnostics,
+            }),
+        }
+    }
+}
+
+/// Machine-readable summary of a finished job, for tools driving the editor.
+#[derive(Serialize)]
+pub struct BuildReport<'a> {
+    pub ok: bool,
+    pub command: &'a str,
+    pub duration_ms: u128,
+    pub diagnostics: &'a [Diagnostic],
+}
+
+/// Where finished jobs are reported as JSON lines, if anywhere.
+pub type ReportSink = Box<dyn Write + Send>;
+
+/// Write `report` as one line of JSON.
+fn write_report(out: &mut ReportSink, report: &BuildReport) -> io::Result<()> {
+    serde_json::to_writer(&mut *out, report)?;
+    out.write_all(b"\n")?;
+    out.flush()
+}
+
+/// How a single cargo invocation ended.
+enum Outcome {
+    Finished(BuildResult),
+    /// A newer lint job for the same project arrived; the child was killed
+    /// and the newer job queued in its place.
+    Superseded,
+}
+
+/// User-facing text for a failed `Command::new("cargo")` spawn; a missing
+/// toolchain gets a hint instead of the bare OS error.
+pub fn spawn_error_message(e: &io::Error) -> String {
+    if e.kind() == io::ErrorKind::NotFound {
+        "cargo not found on PATH — install Rust from https://rustup.rs".into()
+    } else {
+        format!("failed to spawn cargo: {e}")
+    }
+}
+
 pub struct BuildWorker;
 
 impl BuildWorker {
-    #[allow(dead_code)]
-    pub fn start() -> (Sender<BuildJob>, Receiver<BuildResult>) {
+    /// Start the build thread. Jobs run one at a time, in order. A lint job
+    /// (check/clippy) kills an in-flight lint for the same project and
+    /// replaces any queued one, so a burst of saves produces a single result
+    /// for the latest state; builds always run to completion.
+    ///
+    /// Each job reports `Started` and then `Ok`/`Err`; `wake` is called after
+    /// every message so the UI can repaint. With a `report` sink, finished
+    /// jobs are also written there as JSON lines until a write fails.
+    pub fn start(
+        report: Option<ReportSink>,
+        wake: impl Fn() + Send + 'static,
+    ) -> (Sender<BuildJob>, Receiver<BuildResult>) {
         let (tx, rx) = unbounded::<BuildJob>();
         let (otx, orx) = unbounded::<BuildResult>();
 
         thread::spawn(move || {
-            while let Ok(job) = rx.recv() {
-                match job {
-                    BuildJob::Check { root } => {
-                        let t0 = Instant::now();
-                        let mut cmd = Command::new("cargo");
-                        cmd.arg("check")
-                            .arg("--message-format=json")
-                            .current_dir(&root)
-                            .stdout(Stdio::piped())
-                            .stderr(Stdio::null());
-
-                        let mut child = match cmd.spawn() {
-                            Ok(c) => c,
-                            Err(e) => {
-                                let _ = otx.send(BuildResult::Err {
-                                    duration_ms: 0,
-                                    diagnostics: vec![Diagnostic {
-                                        file: root.clone(),
-                                        line: 0,
-                                        col: 0,
-                                        msg: format!("failed to spawn cargo: {e}"),
-                                    }],
-                                });
-                                continue;
-                            }
-                        };
-
-                        let stdout = child.stdout.take().expect("stdout");
-                        let reader = std::io::BufReader::new(stdout);
-                        let mut diags = Vec::<Diagnostic>::new();
-
-                        for line in reader.lines().flatten() {
-                            if let Ok(msg) = serde_json::from_str::<CargoMessage>(&line) {
-                                if let Some(diag) = msg.to_diag() {
-                                    diags.push(diag);
-                                }
-                            }
-                        }
-                        let _ = child.wait();
+            let mut report = report;
+            let mut queue = VecDeque::new();
+            loop {
+                if queue.is_empty() {
+                    match rx.recv() {
+                        Ok(j) => enqueue(&mut queue, j),
+                        Err(_) => break,
+                    }
+                }
+                for j in rx.try_iter() {
+                    enqueue(&mut queue, j);
+                }
+                let Some(job) = queue.pop_front() else {
+                    continue;
+                };
 
-                        let dt = t0.elapsed().as_millis();
-                        if diags.is_empty() {
-                            let _ = otx.send(BuildResult::Ok { duration_ms: dt });
-                        } else {
-                            let _ = otx.send(BuildResult::Err {
-                                duration_ms: dt,
-                                diagnostics: diags,
-                            });
+                let (_, root, _) = job.parts();
+                let _ = otx.send(BuildResult::Started {
+                    root: root.clone(),
+                    command: job.describe(),
+                });
+                wake();
+                match run_cargo(&job, &rx, &mut queue) {
+                    Outcome::Finished(res) => {
+                        // a reader that went away shouldn't stop the editor's builds
+                        if let Some(out) = &mut report
+                            && let Some(r) = res.report()
+                            && write_report(out, &r).is_err()
+                        {
+                            report = None;
                         }
+                        let _ = otx.send(res);
+                        wake();
                     }
+                    Outcome::Superseded => {}
                 }
             }
         });
@@ -88,22 +206,150 @@ impl BuildWorker {
     }
 }
 
+/// Queue `job` behind the others, dropping queued jobs it supersedes.
+fn enqueue(queue: &mut VecDeque<BuildJob>, job: BuildJob) {
+    queue.retain(|older| !job.supersedes(older));
+    queue.push_back(job);
+}
+
+fn run_cargo(job: &BuildJob, jobs: &Receiver<BuildJob>, queue: &mut VecDeque<BuildJob>) -> Outcome {
+    let (subcommand, root, release) = job.parts();
+    let command = job.describe();
+
+    let t0 = Instant::now();
+    let mut cmd = Command::new("cargo");
+    cmd.arg(subcommand).arg("--message-format=json");
+    if release {
+        cmd.arg("--release");
+    }
+    cmd.current_dir(root)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    let mut child = match cmd.spawn() {
+        Ok(c) => c,
+        Err(e) => {
+            return Outcome::Finished(BuildResult::Err {
+                command,
+                duration_ms: 0,
+                diagnostics: vec![Diagnostic {
+                    file: root.clone(),
+                    line: 0,
+                    col: 0,
+                    line_end: 0,
+                    col_end: 0,
+                    msg: spawn_error_message(&e),
+                }],
+            });
+        }
+    };
+
+    // stderr has cargo's own errors (bad manifest, resolution failures);
+    // drained alongside so a chatty cargo can't block on a full pipe
+    let mut stderr = child.stderr.take().expect("stderr");
+    let stderr = thread::spawn(move || {
+        let mut text = String::new();
+        let _ = stderr.read_to_string(&mut text);
+        text
+    });
+
+    // Parse stdout on a helper thread so this one can watch for newer jobs.
+    let stdout = child.stdout.take().expect("stdout");
+    let (done_tx, done_rx) = unbounded::<Vec<Diagnostic>>();
+    thread::spawn(move || {
+        let reader = std::io::BufReader::new(stdout);
+        let mut diags = Vec::<Diagnostic>::new();
+
+        for line in reader.lines().map_while(Result::ok) {
+            if let Ok(msg) = serde_json::from_str::<CargoMessage>(&line)
+                && let Some(diag) = msg.into_diag()
+            {
+                diags.push(diag);
+            }
+        }
+        let _ = done_tx.send(diags);
+    });
+
+    // jobs arriving meanwhile queue up, unless they make this one stale
+    let diags = loop {
+        select! {
+            recv(done_rx) -> diags => break diags.unwrap_or_default(),
+            recv(jobs) -> newer => match newer {
+                Ok(newer) => {
+                    let stale = newer.supersedes(job);
+                    enqueue(queue, newer);
+                    if stale {
+                        let _ = child.kill();
+                        let _ = child.wait();
+                        return Outcome::Superseded;
+                    }
+                }
+                // editor is gone; let cargo finish on its own
+                Err(_) => break done_rx.recv().unwrap_or_default(),
+            },
+        }
+    };
+    let success = child.wait().is_ok_and(|s| s.success());
+    let stderr = stderr.join().unwrap_or_default();
+
+    let dt = t0.elapsed().as_millis();
+    Outcome::Finished(finish(command, root, dt, success, diags, &stderr))
+}
+
+/// The result of a cargo run that exited with `success`: failed if cargo
+/// did or any diagnostic is an error. A failure without compiler errors
+/// (a broken Cargo.toml, say) gets one from cargo's stderr, so it isn't
+/// mistaken for a build with nothing to report.
+fn finish(
+    command: String,
+    root: &Path,
+    duration_ms: u128,
+    success: bool,
+    mut diagnostics: Vec<Diagnostic>,
+    stderr: &str,
+) -> BuildResult {
+    let has_errors = diagnostics.iter().any(|d| d.msg.starts_with("[error]"));
+    if success && !has_errors {
+        return BuildResult::Ok {
+            command,
+            duration_ms,
+            diagnostics,
+        };
+    }
+    if !has_errors {
+        let lines = || stderr.lines().map(str::trim).filter(|l| !l.is_empty());
+        let reason = lines()
+            .find(|l| l.starts_with("error"))
+            .or_else(|| lines().next_back())
+            .unwrap_or("no output");
+        diagnostics.push(Diagnostic {
+            file: root.to_path_buf(),
+            line: 0,
+            col: 0,
+            line_end: 0,
+            col_end: 0,
+            msg: format!("[error] {command} failed: {reason}"),
+        });
+    }
+    BuildResult::Err {
+        command,
+        duration_ms,
+        diagnostics,
+    }
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(tag = "reason", rename_all = "kebab-case")]
 enum CargoMessage {
     #[serde(rename_all = "camelCase")]
-    CompilerMessage { message: RustcMessage },
+    CompilerMessage { message: MessageDetail },
     #[serde(other)]
     Other,
 }
 
-#[derive(Debug, Deserialize)]
-struct RustcMessage {
-    message: MessageDetail,
-}
-
 #[derive(Debug, Deserialize)]
 struct MessageDetail {
+    #[allow(dead_code)]
     code: Option<Code>,
     message: String,  // the human-readable text
     level: String,    // "error", "warning", etc.
@@ -112,6 +358,7 @@ struct MessageDetail {
 
 #[derive(Debug, Deserialize)]
 struct Code {
+    #[allow(dead_code)]
     code: String,
 }
 
@@ -120,27 +367,193 @@ struct Span {
     file_name: String,
     line_start: u32,
     column_start: u32,
+    line_end: u32,
+    column_end: u32,
+    #[serde(default)]
+    is_primary: bool,
 }
 
 impl CargoMessage {
-    fn to_diag(self) -> Option<Diagnostic> {
+    fn into_diag(self) -> Option<Diagnostic> {
         match self {
             CargoMessage::CompilerMessage { message } => {
-                // spans are under message.message.spans
-                let span = message.message.spans.get(0)?;
+                // the primary span is where rustc points its caret, others
+                // are secondary labels
+                let spans = &message.spans;
+                let span = spans
+                    .iter()
+                    .find(|s| s.is_primary)
+                    .or_else(|| spans.first())?;
                 Some(Diagnostic {
                     file: PathBuf::from(&span.file_name),
                     line: span.line_start,
                     col: span.column_start,
+                    line_end: span.line_end,
+                    col_end: span.column_end,
                     // level + human message path
-                    msg: format!(
-                        "[{}] {}",
-                        message.message.level,
-                        message.message.message.trim()
-                    ),
+                    msg: format!("[{}] {}", message.level, message.message.trim()),
                 })
             }
             CargoMessage::Other => None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diag(line: &str) -> Diagnostic {
+        serde_json::from_str::<CargoMessage>(line)
+            .expect("valid cargo message")
+            .into_diag()
+            .expect("compiler message with a span")
+    }
+
+    /// `cargo check --release --message-format=json` output for an unused
+    /// variable; the release profile doesn't change the message shape.
+    const RELEASE_WARNING: &str = r#"{"reason":"compiler-message","package_id":"path+file:///tmp/jt#0.1.0","manifest_path":"/tmp/jt/Cargo.toml","target":{"kind":["bin"],"crate_types":["bin"],"name":"jt","src_path":"/tmp/jt/src/main.rs","edition":"2024","doc":true,"doctest":false,"test":true},"message":{"rendered":"warning: unused variable: `unused`\n --> src/main.rs:2:9\n  |\n2 |     let unused = 1;\n  |         ^^^^^^ help: if this is intentional, prefix it with an underscore: `_unused`\n  |\n  = note: `#[warn(unused_variables)]` (part of `#[warn(unused)]`) on by default\n\n","$message_type":"diagnostic","children":[{"children":[],"code":null,"level":"note","message":"`#[warn(unused_variables)]` (part of `#[warn(unused)]`) on by default","rendered":null,"spans":[]},{"children":[],"code":null,"level":"help","message":"if this is intentional, prefix it with an underscore","rendered":null,"spans":[{"byte_end":26,"byte_start":20,"column_end":15,"column_start":9,"expansion":null,"file_name":"src/main.rs","is_primary":true,"label":null,"line_end":2,"line_start":2,"suggested_replacement":"_unused","suggestion_applicability":"MachineApplicable","text":[{"highlight_end":15,"highlight_start":9,"text":"    let unused = 1;"}]}]}],"level":"warning","message":"unused variable: `unused`","spans":[{"byte_end":26,"byte_start":20,"column_end":15,"column_start":9,"expansion":null,"file_name":"src/main.rs","is_primary":true,"label":null,"line_end":2,"line_start":2,"suggested_replacement":null,"suggestion_applicability":null,"text":[{"highlight_end":15,"highlight_start":9,"text":"    let unused = 1;"}]}],"code":{"code":"unused_variables","explanation":null}}}"#;
+
+    #[test]
+    fn parses_release_mode_compiler_message() {
+        let d = diag(RELEASE_WARNING);
+        assert_eq!(d.file, PathBuf::from("src/main.rs"));
+        assert_eq!((d.line, d.col), (2, 9));
+        assert_eq!((d.line_end, d.col_end), (2, 15));
+        assert_eq!(d.msg, "[warning] unused variable: `unused`");
+    }
+
+    /// E0382 from `cargo check`; the primary span (the use after move) is
+    /// second, between the "moved here" and "move occurs" labels. The long
+    /// `explanation` text is cut to its first line.
+    const MULTI_SPAN_ERROR: &str = r#"{"reason":"compiler-message","package_id":"path+file:///tmp/jt#0.1.0","manifest_path":"/tmp/jt/Cargo.toml","target":{"kind":["bin"],"crate_types":["bin"],"name":"jt","src_path":"/tmp/jt/src/main.rs","edition":"2024","doc":true,"doctest":false,"test":true},"message":{"rendered":"error[E0382]: borrow of moved value: `v`\n --> src/main.rs:5:27\n  |\n3 |     let v = vec![1];\n  |         - move occurs because `v` has type `Vec<i32>`, which does not implement the `Copy` trait\n4 |     let w = v;\n  |             - value moved here\n5 |     println!(\"{:?} {:?}\", v, w);\n  |                           ^ value borrowed here after move\n  |\nhelp: consider cloning the value if the performance cost is acceptable\n  |\n4 |     let w = v.clone();\n  |              ++++++++\n\n","$message_type":"diagnostic","children":[{"children":[],"code":null,"level":"help","message":"consider cloning the value if the performance cost is acceptable","rendered":null,"spans":[{"byte_end":66,"byte_start":66,"column_end":14,"column_start":14,"expansion":null,"file_name":"src/main.rs","is_primary":true,"label":null,"line_end":4,"line_start":4,"suggested_replacement":".clone()","suggestion_applicability":"MachineApplicable","text":[{"highlight_end":14,"highlight_start":14,"text":"    let w = v;"}]}]}],"level":"error","message":"borrow of moved value: `v`","spans":[{"byte_end":66,"byte_start":65,"column_end":14,"column_start":13,"expansion":null,"file_name":"src/main.rs","is_primary":false,"label":"value moved here","line_end":4,"line_start":4,"suggested_replacement":null,"suggestion_applicability":null,"text":[{"highlight_end":14,"highlight_start":13,"text":"    let w = v;"}]},{"byte_end":95,"byte_start":94,"column_end":28,"column_start":27,"expansion":{"def_site_span":{"byte_end":0,"byte_start":0,"column_end":1,"column_start":1,"expansion":null,"file_name":"src/main.rs","is_primary":false,"label":null,"line_end":1,"line_start":1,"suggested_replacement":null,"suggestion_applicability":null,"text":[]},"macro_decl_name":"desugaring of format string literal","span":{"byte_end":4440,"byte_start":4407,"column_end":61,"column_start":28,"expansion":{"def_site_span":{"byte_end":36263,"byte_start":36236,"column_end":32,"column_start":5,"expansion":null,"file_name":"/rustc/59807616e1fa2540724bfbac14d7976d7e4a3860/library/core/src/macros/mod.rs","is_primary":false,"label":null,"line_end":1029,"line_start":1029,"suggested_replacement":null,"suggestion_applicability":null,"text":[]},"macro_decl_name":"$crate::format_args_nl!","span":{"byte_end":4440,"byte_start":4407,"column_end":61,"column_start":28,"expansion":{"def_site_span":{"byte_end":4305,"byte_start":4285,"column_end":21,"column_start":1,"expansion":null,"file_name":"/rustc/59807616e1fa2540724bfbac14d7976d7e4a3860/library/std/src/macros.rs","is_primary":false,"label":null,"line_end":138,"line_start":138,"suggested_replacement":null,"suggestion_applicability":null,"text":[]},"macro_decl_name":"println!","span":{"byte_end":99,"byte_start":72,"column_end":32,"column_start":5,"expansion":null,"file_name":"src/main.rs","is_primary":false,"label":null,"line_end":5,"line_start":5,"suggested_replacement":null,"suggestion_applicability":null,"text":[{"highlight_end":32,"highlight_start":5,"text":"    println!(\"{:?} {:?}\", v, w);"}]}},"file_name":"/rustc/59807616e1fa2540724bfbac14d7976d7e4a3860/library/std/src/macros.rs","is_primary":false,"label":null,"line_end":143,"line_start":143,"suggested_replacement":null,"suggestion_applicability":null,"text":[]}},"file_name":"/rustc/59807616e1fa2540724bfbac14d7976d7e4a3860/library/std/src/macros.rs","is_primary":false,"label":null,"line_end":143,"line_start":143,"suggested_replacement":null,"suggestion_applicability":null,"text":[]}},"file_name":"src/main.rs","is_primary":true,"label":"value borrowed here after move","line_end":5,"line_start":5,"suggested_replacement":null,"suggestion_applicability":null,"text":[{"highlight_end":28,"highlight_start":27,"text":"    println!(\"{:?} {:?}\", v, w);"}]},{"byte_end":41,"byte_start":40,"column_end":10,"column_start":9,"expansion":null,"file_name":"src/main.rs","is_primary":false,"label":"move occurs because `v` has type `Vec<i32>`, which does not implement the `Copy` trait","line_end":3,"line_start":3,"suggested_replacement":null,"suggestion_applicability":null,"text":[{"highlight_end":10,"highlight_start":9,"text":"    let v = vec![1];"}]}],"code":{"code":"E0382","explanation":"A variable was used after its contents have been moved elsewhere.\n"}}}"#;
+
+    #[test]
+    fn picks_primary_span_over_first() {
+        let d = diag(MULTI_SPAN_ERROR);
+        assert_eq!((d.line, d.col), (5, 27));
+        assert_eq!((d.line_end, d.col_end), (5, 28));
+        assert_eq!(d.msg, "[error] borrow of moved value: `v`");
+    }
+
+    /// `cargo clippy --message-format=json` output; lints arrive as ordinary
+    /// compiler messages with a `clippy::` code.
+    const CLIPPY_LINT: &str = r#"{"reason":"compiler-message","package_id":"path+file:///tmp/jt#0.1.0","manifest_path":"/tmp/jt/Cargo.toml","target":{"kind":["bin"],"crate_types":["bin"],"name":"jt","src_path":"/tmp/jt/src/main.rs","edition":"2024","doc":true,"doctest":false,"test":true},"message":{"rendered":"warning: length comparison to zero\n --> src/main.rs:3:8\n  |\n3 |     if v.len() == 0 {\n  |        ^^^^^^^^^^^^ help: using `is_empty` is clearer and more explicit: `v.is_empty()`\n  |\n  = help: for further information visit https://rust-lang.github.io/rust-clippy/rust-1.95.0/index.html#len_zero\n  = note: `#[warn(clippy::len_zero)]` on by default\n\n","$message_type":"diagnostic","children":[{"children":[],"code":null,"level":"help","message":"for further information visit https://rust-lang.github.io/rust-clippy/rust-1.95.0/index.html#len_zero","rendered":null,"spans":[]},{"children":[],"code":null,"level":"note","message":"`#[warn(clippy::len_zero)]` on by default","rendered":null,"spans":[]},{"children":[],"code":null,"level":"help","message":"using `is_empty` is clearer and more explicit","rendered":null,"spans":[{"byte_end":52,"byte_start":40,"column_end":20,"column_start":8,"expansion":null,"file_name":"src/main.rs","is_primary":true,"label":null,"line_end":3,"line_start":3,"suggested_replacement":"v.is_empty()","suggestion_applicability":"MachineApplicable","text":[{"highlight_end":20,"highlight_start":8,"text":"    if v.len() == 0 {"}]}]}],"level":"warning","message":"length comparison to zero","spans":[{"byte_end":52,"byte_start":40,"column_end":20,"column_start":8,"expansion":null,"file_name":"src/main.rs","is_primary":true,"label":null,"line_end":3,"line_start":3,"suggested_replacement":null,"suggestion_applicability":null,"text":[{"highlight_end":20,"highlight_start":8,"text":"    if v.len() == 0 {"}]}],"code":{"code":"clippy::len_zero","explanation":null}}}"#;
+
+    #[test]
+    fn only_lints_for_the_same_root_are_superseded() {
+        let (a, b) = (PathBuf::from("/a"), PathBuf::from("/b"));
+        let mut queue = VecDeque::new();
+        enqueue(
+            &mut queue,
+            BuildJob::Build {
+                root: a.clone(),
+                release: false,
+            },
+        );
+        enqueue(
+            &mut queue,
+            BuildJob::Check {
+                root: a.clone(),
+                release: false,
+            },
+        );
+        enqueue(
+            &mut queue,
+            BuildJob::Check {
+                root: b.clone(),
+                release: false,
+            },
+        );
+        enqueue(
+            &mut queue,
+            BuildJob::Clippy {
+                root: a.clone(),
+                release: true,
+            },
+        );
+        enqueue(
+            &mut queue,
+            BuildJob::Build {
+                root: a.clone(),
+                release: true,
+            },
+        );
+
+        let queued: Vec<String> = queue
+            .iter()
+            .map(|j| format!("{} {}", j.describe(), j.parts().1.display()))
+            .collect();
+        assert_eq!(
+            queued,
+            [
+                "cargo build /a",
+                "cargo check /b",
+                "cargo clippy --release /a",
+                "cargo build --release /a",
+            ]
+        );
+        let check = BuildJob::Check {
+            root: a.clone(),
+            release: false,
+        };
+        assert!(
+            !check.supersedes(&queue[0]),
+            "a check must not kill a build"
+        );
+    }
+
+    #[test]
+    fn warnings_alone_pass() {
+        let warning = diag(RELEASE_WARNING);
+        let res = finish(
+            "cargo check".into(),
+            Path::new("/p"),
+            5,
+            true,
+            vec![warning],
+            "",
+        );
+        let BuildResult::Ok { diagnostics, .. } = res else {
+            panic!("a build with only warnings failed");
+        };
+        assert_eq!(diagnostics.len(), 1);
+    }
+
+    #[test]
+    fn cargo_failure_without_messages_fails() {
+        let stderr = "error: failed to parse manifest at `/p/Cargo.toml`\n\nCaused by:\n  missing field `name`\n";
+        let res = finish(
+            "cargo check".into(),
+            Path::new("/p"),
+            5,
+            false,
+            Vec::new(),
+            stderr,
+        );
+        let BuildResult::Err { diagnostics, .. } = res else {
+            panic!("a failed cargo run passed");
+        };
+        let [d] = &diagnostics[..] else {
+            panic!("expected one diagnostic, got {diagnostics:?}");
+        };
+        assert_eq!(
+            d.msg,
+            "[error] cargo check failed: error: failed to parse manifest at `/p/Cargo.toml`"
+        );
+    }
+
+    #[test]
+    fn parses_clippy_lint() {
+        let msg: CargoMessage = serde_json::from_str(CLIPPY_LINT).unwrap();
+        let CargoMessage::CompilerMessage { message } = &msg else {
+            panic!("expected a compiler message");
+        };
+        let code = message.code.as_ref().map(|c| c.code.as_str());
+        assert_eq!(code, Some("clippy::len_zero"));
+
+        let d = msg.into_diag().unwrap();
+        assert_eq!((d.line, d.col), (3, 8));
+        assert_eq!(d.msg, "[warning] length comparison to zero");
+    }
+}
@@ -19,21 +19,27 @@ impl ExportResult {
 }
 
 /// Run the metadata exporter:
-/// `cargo run --bin export_schema --features bandana_export`
+/// `cargo run --bin <bin> --features <features>`
 ///
 /// - `root`: your game workspace root (where Cargo.toml for the game lives)
+/// - `bin`: exporter binary, `export_schema` by default (see `ProjectConfig`)
+/// - `features`: features to enable; `--features` is omitted when empty
 /// - `extra_env`: optional `(KEY, VALUE)` environment pairs to inject
 ///
 /// This call is synchronous: it blocks until the export finishes and returns
 /// the collected stdout/stderr so you can display them in your UI console.
-pub fn export_schema(root: &Path, extra_env: &[(&str, &str)]) -> io::Result<ExportResult> {
+pub fn export_schema(
+    root: &Path,
+    bin: &str,
+    features: &[&str],
+    extra_env: &[(&str, &str)],
+) -> io::Result<ExportResult> {
     let mut cmd = Command::new("cargo");
-    cmd.arg("run")
-        .arg("--bin")
-        .arg("export_schema")
-        .arg("--features")
-        .arg("bandana_export")
-        .current_dir(root);
+    cmd.arg("run").arg("--bin").arg(bin);
+    if !features.is_empty() {
+        cmd.arg("--features").arg(features.join(","));
+    }
+    cmd.current_dir(root);
 
     for (k, v) in extra_env {
         cmd.env(k, v);
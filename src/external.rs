@@ -0,0 +1,146 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Env var holding the command used to open a source location, e.g.
+/// `BANDANA_EDITOR="zed {file}:{line}:{col}"`. `{file}`, `{line}` and `{col}`
+/// are substituted; without any placeholder `file:line:col` is appended.
+pub const EDITOR_ENV: &str = "BANDANA_EDITOR";
+
+/// Open `file` at `line:col` in the user's editor.
+///
+/// - `root`: project root; rustc reports paths relative to it
+///
+/// Tries, in order: `$BANDANA_EDITOR`, `code --goto`, `$EDITOR +line file`,
+/// and finally the platform "open" command (which loses the position).
+/// The editor is spawned detached; we don't wait for it.
+pub fn open_in_editor(root: &Path, file: &Path, line: u32, col: u32) -> io::Result<()> {
+    let path = resolve(root, file);
+    let file_s = path.display().to_string();
+    let loc = format!("{file_s}:{line}:{col}");
+
+    if let Ok(template) = std::env::var(EDITOR_ENV) {
+        let has_placeholder = template.contains("{file}");
+        let mut parts = template.split_whitespace().map(|p| {
+            p.replace("{file}", &file_s)
+                .replace("{line}", &line.to_string())
+                .replace("{col}", &col.to_string())
+        });
+        let Some(prog) = parts.next() else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{EDITOR_ENV} is empty"),
+            ));
+        };
+        let mut cmd = Command::new(prog);
+        cmd.args(parts);
+        if !has_placeholder {
+            cmd.arg(&loc);
+        }
+        return cmd.spawn().map(|_| ());
+    }
+
+    match Command::new("code").arg("--goto").arg(&loc).spawn() {
+        Ok(_) => return Ok(()),
+        Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+        Err(_) => {}
+    }
+
+    if let Ok(editor) = std::env::var("EDITOR")
+        && !editor.trim().is_empty()
+    {
+        let mut parts = editor.split_whitespace();
+        if let Some(prog) = parts.next() {
+            return Command::new(prog)
+                .args(parts)
+                .arg(format!("+{line}"))
+                .arg(&path)
+                .spawn()
+                .map(|_| ());
+        }
+    }
+
+    open_path(&path)
+}
+
+/// Hand a path to the OS default handler (`open` / `xdg-open` / `start`).
+pub fn open_path(path: &Path) -> io::Result<()> {
+    let mut cmd = if cfg!(target_os = "windows") {
+        let mut c = Command::new("cmd");
+        c.args(["/C", "start", ""]);
+        c
+    } else if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else {
+        Command::new("xdg-open")
+    };
+    cmd.arg(path).spawn().map(|_| ())
+}
+
+/// Show `dir` in the OS file manager (`explorer` / `open` / `xdg-open`).
+pub fn reveal_in_file_manager(dir: &Path) -> io::Result<()> {
+    let prog = if cfg!(target_os = "windows") {
+        "explorer"
+    } else if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    Command::new(prog).arg(dir).spawn().map(|_| ())
+}
+
+/// Open a terminal window whose working directory is `dir`.
+///
+/// Windows starts a new `cmd` console, macOS opens Terminal.app. Elsewhere
+/// `$TERMINAL` is tried first, then the usual emulators until one exists.
+pub fn open_terminal(dir: &Path) -> io::Result<()> {
+    if cfg!(target_os = "windows") {
+        return Command::new("cmd")
+            .args(["/C", "start", "cmd"])
+            .current_dir(dir)
+            .spawn()
+            .map(|_| ());
+    }
+    if cfg!(target_os = "macos") {
+        return Command::new("open")
+            .args(["-a", "Terminal"])
+            .arg(dir)
+            .spawn()
+            .map(|_| ());
+    }
+
+    let preferred = std::env::var("TERMINAL")
+        .ok()
+        .filter(|t| !t.trim().is_empty());
+    let candidates = preferred.into_iter().chain(
+        [
+            "x-terminal-emulator",
+            "gnome-terminal",
+            "konsole",
+            "xfce4-terminal",
+            "alacritty",
+            "kitty",
+            "xterm",
+        ]
+        .map(String::from),
+    );
+    for prog in candidates {
+        match Command::new(&prog).current_dir(dir).spawn() {
+            Ok(_) => return Ok(()),
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            Err(_) => {}
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        "no terminal emulator found; set $TERMINAL",
+    ))
+}
+
+fn resolve(root: &Path, file: &Path) -> PathBuf {
+    if file.is_absolute() {
+        file.to_path_buf()
+    } else {
+        root.join(file)
+    }
+}
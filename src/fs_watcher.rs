@@ -1,70 +1,302 @@
-use crossbeam::channel::{Receiver, Sender, unbounded};
+use crossbeam::channel::{Receiver, RecvTimeoutError, Sender, unbounded};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
 use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
-use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::{Duration, Instant};
 
+/// User-tunable watcher behaviour, shared with the UI so edits apply live.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatchSettings {
+    /// Quiet period after the last event before a burst is reported.
+    pub debounce_ms: u64,
+    /// Run `cargo check` when sources change; otherwise only on request.
+    pub auto_check: bool,
+    /// Use `cargo clippy` instead of `cargo check` for those runs.
+    pub clippy: bool,
+}
+
+impl Default for WatchSettings {
+    fn default() -> Self {
+        Self {
+            debounce_ms: 250,
+            auto_check: true,
+            clippy: false,
+        }
+    }
+}
+
+pub type SharedWatchSettings = Arc<Mutex<WatchSettings>>;
+
+/// A debounced burst of changes, split by the watched input it touched.
+/// One burst can produce several of these, one per kind.
+#[derive(Debug, Clone)]
+#[allow(clippy::enum_variant_names)] // reads as "source changed" etc. at match sites
+pub enum FsChange {
+    /// Something under `src/`: worth a `cargo check`.
+    SourceChanged(Vec<PathBuf>),
+    /// Something under `design/` (scene files, `.schema.ron`).
+    SceneChanged(Vec<PathBuf>),
+    /// `Cargo.toml` or `project.ron`.
+    ManifestChanged(Vec<PathBuf>),
+}
+
+impl FsChange {
+    pub fn paths(&self) -> &[PathBuf] {
+        match self {
+            FsChange::SourceChanged(p)
+            | FsChange::SceneChanged(p)
+            | FsChange::ManifestChanged(p) => p,
+        }
+    }
+}
+
+/// Files whose patterns we honor on top of the built-in target/ & .git/ skip.
+/// `.bandanaignore` uses gitignore syntax for editor-only excludes.
+const IGNORE_FILES: [&str; 2] = [".gitignore", ".bandanaignore"];
+
+/// Owns the `notify` watcher and the debounce thread. `stop` (or dropping the
+/// worker) tears the watcher down, which disconnects the thread's channel; the
+/// thread then exits, dropping its `Sender<Event>` so the receiver sees the
+/// channel close. Nothing is reported after `stop` returns.
 pub struct WatchWorker {
-    _thread: thread::JoinHandle<()>,
+    watcher: Option<RecommendedWatcher>,
+    thread: Option<thread::JoinHandle<()>>,
 }
 
 impl WatchWorker {
-    pub fn start(root: PathBuf, tx: Sender<Event>) -> Self {
-        let handle = thread::spawn(move || {
-            let (inner_tx, inner_rx) = unbounded::<notify::Result<Event>>();
+    /// Changes are reported regardless of `auto_check`; deciding whether a
+    /// change warrants a build is up to the receiver.
+    pub fn start(
+        root: PathBuf,
+        settings: SharedWatchSettings,
+        tx: Sender<FsChange>,
+    ) -> notify::Result<Self> {
+        let (inner_tx, inner_rx) = unbounded::<Event>();
 
-            let mut watcher = RecommendedWatcher::new(
-                move |res| {
-                    let _ = inner_tx.send(res);
-                },
-                Config::default(),
-            )
-            .expect("watcher");
-
-            // Watch only the interesting inputs (avoid target/ & .git/ loops)
-            let _ = watcher.watch(&root.join("src"), RecursiveMode::Recursive);
-            let _ = watcher.watch(&root.join("design"), RecursiveMode::Recursive);
-            let _ = watcher.watch(&root.join("Cargo.toml"), RecursiveMode::NonRecursive);
-            let _ = watcher.watch(&root.join("project.ron"), RecursiveMode::NonRecursive);
-
-            // Simple debounce window
-            let mut last_fire = Instant::now()
-                .checked_sub(Duration::from_secs(1))
-                .unwrap_or_else(Instant::now);
-
-            while let Ok(res) = inner_rx.recv() {
-                let Ok(event) = res else { continue };
-
-                // Skip noisy event kinds quickly
-                if matches!(event.kind, EventKind::Access(_) | EventKind::Other) {
-                    continue;
+        let mut watcher = RecommendedWatcher::new(
+            move |res: notify::Result<Event>| {
+                if let Ok(event) = res {
+                    let _ = inner_tx.send(event);
                 }
+            },
+            Config::default(),
+        )?;
 
-                // Ignore anything under target/ or .git/
-                let interesting = event.paths.iter().any(|p| {
-                    let rel = p.strip_prefix(&root).unwrap_or(p);
-                    let s = rel.to_string_lossy();
-                    !(s.starts_with("target/")
-                        || s == "target"
-                        || s.contains("/target/")
-                        || s.starts_with(".git/")
-                        || s == ".git"
-                        || s.contains("/.git/"))
-                });
-                if !interesting {
-                    continue;
-                }
+        // Watch only the interesting inputs (avoid target/ & .git/ loops)
+        let _ = watcher.watch(&root.join("src"), RecursiveMode::Recursive);
+        let _ = watcher.watch(&root.join("design"), RecursiveMode::Recursive);
+        let _ = watcher.watch(&root.join("Cargo.toml"), RecursiveMode::NonRecursive);
+        let _ = watcher.watch(&root.join("project.ron"), RecursiveMode::NonRecursive);
+        for name in IGNORE_FILES {
+            let _ = watcher.watch(&root.join(name), RecursiveMode::NonRecursive);
+        }
+
+        let handle = thread::spawn(move || {
+            let mut ignore = build_ignore(&root);
+            debounce(
+                &inner_rx,
+                || Duration::from_millis(settings.lock().unwrap().debounce_ms),
+                |event| {
+                    // ignore rules changed: rebuild the matcher, no build needed
+                    if event.paths.iter().any(|p| is_ignore_file(&root, p)) {
+                        ignore = build_ignore(&root);
+                        return false;
+                    }
+                    is_interesting(&root, &ignore, event)
+                },
+                |event| {
+                    for change in classify(&root, event.paths) {
+                        let _ = tx.send(change);
+                    }
+                },
+            );
+        });
+
+        Ok(Self {
+            watcher: Some(watcher),
+            thread: Some(handle),
+        })
+    }
+
+    /// Stop watching and wait for the debounce thread to exit.
+    pub fn stop(&mut self) {
+        // dropping the watcher drops `inner_tx`, which ends the thread's loop
+        self.watcher = None;
+        if let Some(handle) = self.thread.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for WatchWorker {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
 
-                // Debounce bursts to a single notification
-                if last_fire.elapsed() < Duration::from_millis(250) {
+/// Trailing-edge debounce: events passing `keep` are merged into one burst,
+/// handed to `emit` once nothing has arrived for `window()`. Returns when `rx`
+/// disconnects (the watcher was dropped), discarding any pending burst.
+fn debounce(
+    rx: &Receiver<Event>,
+    window: impl Fn() -> Duration,
+    mut keep: impl FnMut(&Event) -> bool,
+    mut emit: impl FnMut(Event),
+) {
+    let mut pending: Option<Event> = None;
+    let mut deadline = Instant::now();
+
+    loop {
+        let res = if pending.is_some() {
+            rx.recv_deadline(deadline)
+        } else {
+            rx.recv().map_err(|_| RecvTimeoutError::Disconnected)
+        };
+
+        match res {
+            Ok(event) => {
+                if !keep(&event) {
                     continue;
                 }
-                last_fire = Instant::now();
-
-                let _ = tx.send(event);
+                deadline = Instant::now() + window();
+                match &mut pending {
+                    Some(acc) => merge_event(acc, event),
+                    None => pending = Some(event),
+                }
             }
+            Err(RecvTimeoutError::Timeout) => {
+                if let Some(event) = pending.take() {
+                    emit(event);
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+}
+
+fn build_ignore(root: &Path) -> Gitignore {
+    let mut builder = GitignoreBuilder::new(root);
+    for name in IGNORE_FILES {
+        let path = root.join(name);
+        if path.exists() {
+            // a malformed line only drops that pattern; keep the rest
+            let _ = builder.add(path);
+        }
+    }
+    builder.build().unwrap_or_else(|_| Gitignore::empty())
+}
+
+fn is_ignore_file(root: &Path, p: &Path) -> bool {
+    IGNORE_FILES.iter().any(|name| p == root.join(name))
+}
+
+fn is_interesting(root: &Path, ignore: &Gitignore, event: &Event) -> bool {
+    // Skip noisy event kinds quickly
+    if matches!(event.kind, EventKind::Access(_) | EventKind::Other) {
+        return false;
+    }
+
+    event.paths.iter().any(|p| {
+        let rel = p.strip_prefix(root).unwrap_or(p);
+        let s = rel.to_string_lossy();
+        // Fast path: anything under target/ or .git/
+        if s.starts_with("target/")
+            || s == "target"
+            || s.contains("/target/")
+            || s.starts_with(".git/")
+            || s == ".git"
+            || s.contains("/.git/")
+        {
+            return false;
+        }
+        // the editor's own recovery/temp files (see ProjectState::write_autosave)
+        if s.ends_with(".autosave") || s.ends_with(".tmp") {
+            return false;
+        }
+        // matched_path_or_any_parents panics on paths outside the root
+        !(p.starts_with(root)
+            && ignore
+                .matched_path_or_any_parents(p, p.is_dir())
+                .is_ignore())
+    })
+}
+
+/// Split `paths` by watched input. Paths outside all of them (shouldn't
+/// happen; the watches are that narrow) are dropped.
+fn classify(root: &Path, paths: Vec<PathBuf>) -> Vec<FsChange> {
+    let (mut source, mut scene, mut manifest) = (Vec::new(), Vec::new(), Vec::new());
+    for p in paths {
+        let rel = p.strip_prefix(root).unwrap_or(&p);
+        if rel.starts_with("src") {
+            source.push(p);
+        } else if rel.starts_with("design") {
+            scene.push(p);
+        } else if rel == Path::new("Cargo.toml") || rel == Path::new("project.ron") {
+            manifest.push(p);
+        }
+    }
+    [
+        (!source.is_empty()).then_some(FsChange::SourceChanged(source)),
+        (!scene.is_empty()).then_some(FsChange::SceneChanged(scene)),
+        (!manifest.is_empty()).then_some(FsChange::ManifestChanged(manifest)),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// Fold `next` into `acc`: latest kind wins, paths are unioned.
+fn merge_event(acc: &mut Event, next: Event) {
+    acc.kind = next.kind;
+    for p in next.paths {
+        if !acc.paths.contains(&p) {
+            acc.paths.push(p);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{CreateKind, ModifyKind};
+
+    fn event(kind: EventKind, path: &str) -> Event {
+        Event::new(kind).add_path(PathBuf::from(path))
+    }
+
+    #[test]
+    fn burst_fires_once_with_the_last_event() {
+        const WINDOW: Duration = Duration::from_millis(100);
+        let (tx, rx) = unbounded::<Event>();
+        let (out_tx, out_rx) = unbounded::<Event>();
+        let worker = thread::spawn(move || {
+            debounce(&rx, || WINDOW, |_| true, |e| out_tx.send(e).unwrap());
         });
 
-        Self { _thread: handle }
+        // each gap is well inside the window, so the burst never goes quiet
+        let paths = ["src/a.rs", "src/b.rs", "src/a.rs", "src/c.rs"];
+        for (i, p) in paths.iter().enumerate() {
+            let kind = if i == 0 {
+                EventKind::Create(CreateKind::File)
+            } else {
+                EventKind::Modify(ModifyKind::Any)
+            };
+            tx.send(event(kind, p)).unwrap();
+            thread::sleep(WINDOW / 4);
+        }
+        assert!(out_rx.try_recv().is_err(), "fired before the burst ended");
+
+        let fired = out_rx.recv_timeout(WINDOW * 10).expect("burst never fired");
+        assert_eq!(fired.kind, EventKind::Modify(ModifyKind::Any));
+        assert!(fired.paths.contains(&PathBuf::from("src/c.rs")));
+        assert_eq!(fired.paths.len(), 3);
+
+        drop(tx);
+        worker.join().unwrap();
+        assert!(out_rx.try_recv().is_err(), "fired more than once");
     }
 }
@@ -0,0 +1,125 @@
+//! Editor tasks run from the command line without opening a window, for
+//! scripts and local CI: `bandana <task> <project>`.
+
+use crate::build::{BuildJob, BuildResult, BuildWorker};
+use crate::build_meta;
+use crate::project::{Diagnostic, ProjectState, Severity};
+use anyhow::{Context, Result};
+use std::path::Path;
+
+pub enum Task {
+    /// `cargo check` in the project, printing its diagnostics.
+    Check,
+    /// Run the project's schema exporter (`export_bin` in project.ron).
+    ExportSchema,
+    /// Validate the design scene; warnings are printed but don't fail.
+    Validate,
+}
+
+impl Task {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "check" => Some(Task::Check),
+            "export-schema" => Some(Task::ExportSchema),
+            "validate" => Some(Task::Validate),
+            _ => None,
+        }
+    }
+}
+
+/// Run `task` for the project in `dir` and print the outcome. Ok(false)
+/// when the task itself failed; Err when it couldn't run at all.
+pub fn run(task: Task, dir: &Path) -> Result<bool> {
+    let p = ProjectState::open(dir)?;
+    match task {
+        Task::Check => check(&p),
+        Task::ExportSchema => export_schema(&p),
+        Task::Validate => Ok(validate(&p)),
+    }
+}
+
+fn check(p: &ProjectState) -> Result<bool> {
+    let (jobs, results) = BuildWorker::start(None, || {});
+    jobs.send(BuildJob::Check {
+        root: p.root.clone(),
+        release: false,
+    })?;
+    loop {
+        match results.recv().context("build worker stopped")? {
+            BuildResult::Started { command, .. } => println!("running {command}"),
+            // Ok only when cargo exited cleanly without errors; warnings pass
+            BuildResult::Ok {
+                command,
+                duration_ms,
+                diagnostics,
+            } => {
+                print_diagnostics(&diagnostics);
+                println!("{command}: ok in {duration_ms} ms");
+                return Ok(true);
+            }
+            BuildResult::Err {
+                command,
+                duration_ms,
+                diagnostics,
+            } => {
+                print_diagnostics(&diagnostics);
+                let errors = diagnostics
+                    .iter()
+                    .filter(|d| d.msg.starts_with("[error]"))
+                    .count();
+                eprintln!("{command}: failed with {errors} error(s) in {duration_ms} ms");
+                return Ok(false);
+            }
+        }
+    }
+}
+
+/// One `file:line:col message` line per diagnostic, on stderr like cargo's.
+fn print_diagnostics(diagnostics: &[Diagnostic]) {
+    for d in diagnostics {
+        eprintln!("{}:{}:{} {}", d.file.display(), d.line, d.col, d.msg);
+    }
+}
+
+fn export_schema(p: &ProjectState) -> Result<bool> {
+    let bin = p.config.export_bin.trim();
+    let features: Vec<&str> = p
+        .config
+        .export_features
+        .iter()
+        .map(String::as_str)
+        .collect();
+    let res = build_meta::export_schema(&p.root, bin, &features, &[])
+        .with_context(|| format!("running the {bin} exporter"))?;
+    print!("{}", res.stdout);
+    eprint!("{}", res.stderr);
+    if !res.success() {
+        println!("export failed (exit {})", res.status);
+    }
+    Ok(res.success())
+}
+
+fn validate(p: &ProjectState) -> bool {
+    if let Some(err) = &p.design_error {
+        println!("error: {err}");
+        return false;
+    }
+    if p.design_scene.is_none() {
+        println!("no design scene (design/initial.scene.ron)");
+        return true;
+    }
+    for issue in &p.validation {
+        let level = match issue.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        println!("{level}: {}: {}", issue.entity, issue.message);
+    }
+    let errors = p
+        .validation
+        .iter()
+        .filter(|i| i.severity == Severity::Error)
+        .count();
+    println!("{} issue(s), {errors} error(s)", p.validation.len());
+    errors == 0
+}
@@ -1,18 +1,84 @@
+mod ansi;
 mod app;
 mod build;
 mod build_meta;
+mod external;
 mod fs_watcher;
+mod headless;
 mod preview;
 mod project;
+mod runner;
 
-use anyhow::Result;
+use anyhow::{Context, Result, bail};
+use eframe::egui;
+use std::path::PathBuf;
+use std::process::ExitCode;
 
-fn main() -> Result<()> {
-    let native_options = eframe::NativeOptions::default();
+enum Mode {
+    Gui(Option<build::ReportSink>),
+    Headless(headless::Task, PathBuf),
+}
+
+/// Command-line options:
+///
+/// * `check|validate|export-schema <project>` — run that task without the
+///   GUI (see `headless`) and exit non-zero if it fails.
+/// * `--report-json` — write every finished check/build to stdout as a line
+///   of JSON (`build::BuildReport`).
+/// * `--report-socket <path>` — same, sent to a listening unix socket.
+fn parse_args() -> Result<Mode> {
+    let mut args = std::env::args().skip(1).peekable();
+    if let Some(task) = args.peek().and_then(|a| headless::Task::parse(a)) {
+        let name = args.next().unwrap_or_default();
+        let dir = args
+            .next()
+            .with_context(|| format!("usage: bandana {name} <project-dir>"))?;
+        if let Some(extra) = args.next() {
+            bail!("unexpected argument after the project: {extra}");
+        }
+        return Ok(Mode::Headless(task, dir.into()));
+    }
+    let mut report: Option<build::ReportSink> = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--report-json" => report = Some(Box::new(std::io::stdout())),
+            #[cfg(unix)]
+            "--report-socket" => {
+                let path = args.next().context("--report-socket needs a path")?;
+                let stream = std::os::unix::net::UnixStream::connect(&path)
+                    .with_context(|| format!("connecting to {path}"))?;
+                report = Some(Box::new(stream));
+            }
+            other => bail!("unknown argument: {other}"),
+        }
+    }
+    Ok(Mode::Gui(report))
+}
+
+fn main() -> Result<ExitCode> {
+    let report = match parse_args()? {
+        Mode::Gui(report) => report,
+        Mode::Headless(task, dir) => {
+            let ok = headless::run(task, &dir)?;
+            return Ok(if ok {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
+            });
+        }
+    };
+    let native_options = eframe::NativeOptions {
+        viewport: egui::ViewportBuilder::default()
+            .with_inner_size([1400.0, 900.0])
+            .with_min_inner_size([800.0, 500.0]),
+        // window size/position and egui state come back on the next launch
+        persist_window: true,
+        ..Default::default()
+    };
     let _ = eframe::run_native(
         "Bevy Editor",
         native_options,
-        Box::new(|cc| Ok(Box::new(app::EditorApp::new(cc)))),
+        Box::new(|cc| Ok(Box::new(app::EditorApp::new(cc, report)))),
     );
-    Ok(())
+    Ok(ExitCode::SUCCESS)
 }
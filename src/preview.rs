@@ -1,64 +1,508 @@
+use bevy::app::PluginGroupBuilder;
 use bevy::prelude::*;
-use crossbeam::channel::{Receiver, TryRecvError};
+use crossbeam::channel::{Receiver, Sender};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::thread;
+use std::time::Duration;
 
-use crate::project::{CompData, SceneDoc}; // your types
+use crate::project::{COMPONENT_TYPES, ComponentDoc, MIN_MESH_DIM, SceneDoc}; // your types
+use std::collections::{HashMap, HashSet};
 
 #[derive(Component)]
 struct PreviewTag; // mark spawned scene entities so we can clear/rebuild
 
+/// The built-in camera from `setup`; disabled while the scene has its own.
+#[derive(Component)]
+struct DefaultCamera;
+
+/// The built-in light from `setup`; hidden while the scene has its own.
+#[derive(Component)]
+struct DefaultLight;
+
+/// The orthographic camera that mirrors the editor's 2D view; active only
+/// while a `TopDownView` is set.
+#[derive(Component)]
+struct TopDownCamera;
+
 #[derive(Resource)]
 struct SceneRx(Receiver<SceneDoc>);
 
+#[derive(Resource)]
+struct ViewRx(Receiver<Option<TopDownView>>);
+
+/// The latest `TopDownView` from the editor; None = scene/default camera.
+#[derive(Resource, Default)]
+struct TopDown(Option<TopDownView>);
+
+/// Framing of the editor's top-down 2D view, mirrored by the preview's
+/// orthographic camera: +x right and +z down on screen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TopDownView {
+    pub center: (f32, f32), // world xz at the middle of the view
+    pub zoom: f32,          // screen pixels per world unit
+}
+
+/// How a preview's Bevy thread is doing.
+pub enum PreviewStatus {
+    Running,
+    Exited,          // the app returned, e.g. its window was closed
+    Crashed(String), // Bevy panicked; the panic message
+}
+
+/// Liveness of a Bevy thread, shared between the thread and its handle.
+#[derive(Clone)]
+struct ThreadState {
+    alive: Arc<AtomicBool>,
+    crash: Arc<std::sync::Mutex<Option<String>>>,
+}
+
+impl ThreadState {
+    fn status(&self) -> PreviewStatus {
+        if self.alive.load(Ordering::Relaxed) {
+            return PreviewStatus::Running;
+        }
+        match self.crash.lock().unwrap().clone() {
+            Some(msg) => PreviewStatus::Crashed(msg),
+            None => PreviewStatus::Exited,
+        }
+    }
+}
+
+/// Flips `ThreadState::alive` off when the thread's work ends, however it
+/// ends, and wakes the editor so it notices.
+struct ExitGuard(Arc<AtomicBool>, Arc<dyn Fn() + Send + Sync>);
+
+impl Drop for ExitGuard {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Relaxed);
+        (self.1)();
+    }
+}
+
+/// Run a Bevy app via `run` on a new thread. A panic (no GPU adapter, a
+/// second winit event loop, ...) is caught and kept as the crash message
+/// instead of silently killing the thread.
+fn spawn_bevy(
+    wake: Arc<dyn Fn() + Send + Sync>,
+    run: impl FnOnce() + Send + 'static,
+) -> (thread::JoinHandle<()>, ThreadState) {
+    let state = ThreadState {
+        alive: Arc::new(AtomicBool::new(true)),
+        crash: Arc::default(),
+    };
+    let shared = state.clone();
+    let th = thread::spawn(move || {
+        let _guard = ExitGuard(shared.alive.clone(), wake);
+        if let Err(panic) = std::panic::catch_unwind(std::panic::AssertUnwindSafe(run)) {
+            let msg = panic
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".into());
+            *shared.crash.lock().unwrap() = Some(msg);
+        }
+    });
+    (th, state)
+}
+
 pub struct PreviewHandle {
-    tx_alive: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    state: ThreadState,
     _thread: thread::JoinHandle<()>,
 }
 
 impl PreviewHandle {
     /// Spawn a Bevy window in a background thread and return a handle.
-    pub fn start(scene_rx: Receiver<SceneDoc>) -> Self {
-        let alive = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(true));
-        let alive_clone = alive.clone();
-
-        let th = thread::spawn(move || {
+    /// `view_rx` switches the window to a top-down camera (Some) and back;
+    /// `wake` is called once the window's thread ends.
+    pub fn start(
+        scene_rx: Receiver<SceneDoc>,
+        view_rx: Receiver<Option<TopDownView>>,
+        wake: impl Fn() + Send + Sync + 'static,
+    ) -> Self {
+        let (th, state) = spawn_bevy(Arc::new(wake), move || {
             let mut app = App::new();
 
-            app.add_plugins(DefaultPlugins.set(WindowPlugin {
-                primary_window: Some(Window {
-                    title: "Preview".into(),
-                    resolution: (960., 540.).into(),
-                    present_mode: bevy::window::PresentMode::AutoVsync,
-                    ..default()
-                }),
-                ..default()
-            }));
+            app.add_plugins(
+                DefaultPlugins
+                    // mesh assets arrive as absolute paths into the project
+                    .set(AssetPlugin {
+                        unapproved_path_mode: bevy::asset::UnapprovedPathMode::Allow,
+                        ..default()
+                    })
+                    .set(WindowPlugin {
+                        primary_window: Some(Window {
+                            title: "Preview".into(),
+                            resolution: (960., 540.).into(),
+                            present_mode: bevy::window::PresentMode::AutoVsync,
+                            ..default()
+                        }),
+                        ..default()
+                    })
+                    // we're not on the main thread; that one belongs to eframe
+                    .set({
+                        let mut winit = bevy::winit::WinitPlugin::<bevy::winit::WakeUp>::default();
+                        winit.run_on_any_thread = true;
+                        winit
+                    }),
+            );
 
-            app.insert_resource(SceneRx(scene_rx));
+            app.insert_resource(SceneRx(scene_rx))
+                .insert_resource(ViewRx(view_rx))
+                .init_resource::<Spawned>()
+                .init_resource::<TopDown>();
 
             // basic scene that matches your game defaults
-            app.add_systems(Startup, setup)
-                .add_systems(Update, (apply_scene_updates,));
+            app.add_systems(Startup, setup).add_systems(
+                Update,
+                (apply_scene_updates, apply_view_updates, select_camera).chain(),
+            );
 
+            // returns when the window closes
             app.run();
-            // When the window closes, the app exits; thread ends.
-            let _ = alive_clone;
         });
 
-        Self {
-            tx_alive: alive,
-            _thread: th,
+        Self { state, _thread: th }
+    }
+
+    pub fn status(&self) -> PreviewStatus {
+        self.state.status()
+    }
+}
+
+/// Size of the image `render_thumbnail` writes.
+const THUMBNAIL_SIZE: (u32, u32) = (480, 270);
+/// Frames rendered before capturing, so meshes/materials are uploaded and
+/// glTF assets have a chance to load.
+const THUMBNAIL_WARMUP_FRAMES: u32 = 30;
+/// Give up if the capture hasn't come back by then.
+const THUMBNAIL_MAX_FRAMES: u32 = 600;
+
+/// Render one frame of `scene` offscreen and save it as a PNG at `out`.
+///
+/// Runs a windowless Bevy app on the calling thread, built with the live
+/// preview's systems (`setup`, `apply_scene_updates`, `select_camera`) so
+/// the thumbnail matches what the preview shows; cameras are pointed at an
+/// image instead of a window. Blocks until the image is written. Asset mesh
+/// paths must be absolute, as for the live preview.
+pub fn render_thumbnail(scene: &SceneDoc, out: &std::path::Path) -> anyhow::Result<()> {
+    use std::sync::Mutex;
+
+    let (scene_tx, scene_rx) = crossbeam::channel::unbounded();
+    let _ = scene_tx.send(scene.clone());
+    let (_, view_rx) = crossbeam::channel::unbounded(); // never switches to top-down
+
+    let result: Arc<Mutex<Option<Result<(), String>>>> = Arc::default();
+    let mut app = App::new();
+    app.add_plugins(offscreen_plugins(Duration::ZERO));
+    add_offscreen_target(&mut app, THUMBNAIL_SIZE);
+
+    app.insert_resource(SceneRx(scene_rx))
+        .insert_resource(ViewRx(view_rx))
+        .init_resource::<Spawned>()
+        .init_resource::<TopDown>()
+        .insert_resource(Thumbnail {
+            out: out.to_path_buf(),
+            frames: 0,
+            result: result.clone(),
+        })
+        .add_systems(Startup, setup)
+        .add_systems(
+            Update,
+            (
+                apply_scene_updates,
+                select_camera,
+                target_offscreen,
+                capture_thumbnail,
+            )
+                .chain(),
+        );
+    app.run();
+
+    let outcome = result.lock().unwrap().take();
+    match outcome {
+        Some(Ok(())) => Ok(()),
+        Some(Err(e)) => anyhow::bail!(e),
+        None => anyhow::bail!("the renderer stopped before capturing a frame"),
+    }
+}
+
+/// Plugins for a windowless preview app: the schedule runner drives frames
+/// every `frame` (zero = as fast as possible), and logging is left to the
+/// editor (or a live preview).
+fn offscreen_plugins(frame: Duration) -> PluginGroupBuilder {
+    DefaultPlugins
+        .set(AssetPlugin {
+            unapproved_path_mode: bevy::asset::UnapprovedPathMode::Allow,
+            ..default()
+        })
+        .set(WindowPlugin {
+            primary_window: None,
+            exit_condition: bevy::window::ExitCondition::DontExit,
+            close_when_requested: false,
+        })
+        .disable::<bevy::winit::WinitPlugin<bevy::winit::WakeUp>>()
+        .disable::<bevy::log::LogPlugin>()
+        // without winit nothing would run past the first frame
+        .add(bevy::app::ScheduleRunnerPlugin::run_loop(frame))
+}
+
+/// The image every camera of an offscreen app renders into.
+#[derive(Resource)]
+struct OffscreenTarget(Handle<Image>);
+
+/// Create a `width` x `height` render target and register it as the app's
+/// `OffscreenTarget`.
+fn add_offscreen_target(app: &mut App, (width, height): (u32, u32)) {
+    use bevy::asset::RenderAssetUsages;
+    use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages};
+
+    let mut image = Image::new_fill(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 255],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image.texture_descriptor.usage =
+        TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_SRC | TextureUsages::RENDER_ATTACHMENT;
+    let target = app.world_mut().resource_mut::<Assets<Image>>().add(image);
+    app.insert_resource(OffscreenTarget(target));
+}
+
+/// Point every camera at the offscreen image (scene cameras arrive with the
+/// window as their target).
+fn target_offscreen(target: Res<OffscreenTarget>, mut cams: Query<&mut Camera>) {
+    use bevy::render::camera::RenderTarget;
+    for mut cam in &mut cams {
+        if !matches!(&cam.target, RenderTarget::Image(t) if t.handle == target.0) {
+            cam.target = RenderTarget::Image(target.0.clone().into());
         }
     }
 }
 
+#[derive(Resource)]
+struct Thumbnail {
+    out: std::path::PathBuf,
+    frames: u32,
+    result: Arc<std::sync::Mutex<Option<Result<(), String>>>>,
+}
+
+/// After the warm-up, request a readback of the image; the observer saves it
+/// and ends the app.
+fn capture_thumbnail(
+    mut commands: Commands,
+    target: Res<OffscreenTarget>,
+    mut thumb: ResMut<Thumbnail>,
+    mut exit: EventWriter<AppExit>,
+) {
+    use bevy::render::view::screenshot::{Screenshot, ScreenshotCaptured};
+
+    thumb.frames += 1;
+    if thumb.frames == THUMBNAIL_WARMUP_FRAMES {
+        let out = thumb.out.clone();
+        let result = thumb.result.clone();
+        commands.spawn(Screenshot::image(target.0.clone())).observe(
+            move |trigger: Trigger<ScreenshotCaptured>, mut exit: EventWriter<AppExit>| {
+                let saved = trigger
+                    .event()
+                    .0
+                    .clone()
+                    .try_into_dynamic()
+                    .map_err(|e| format!("converting the frame: {e}"))
+                    .and_then(|img| {
+                        img.to_rgb8()
+                            .save(&out)
+                            .map_err(|e| format!("writing {}: {e}", out.display()))
+                    });
+                *result.lock().unwrap() = Some(saved);
+                exit.write(AppExit::Success);
+            },
+        );
+    } else if thumb.frames > THUMBNAIL_MAX_FRAMES {
+        *thumb.result.lock().unwrap() = Some(Err("timed out waiting for the frame".into()));
+        exit.write(AppExit::error());
+    }
+}
+
+/// One frame of the embedded preview: tightly packed sRGB RGBA8 rows.
+pub struct PreviewFrame {
+    pub size: [usize; 2], // width, height
+    pub rgba: Vec<u8>,
+}
+
+/// Frame period of the embedded preview (~30 fps).
+const EMBEDDED_FRAME: Duration = Duration::from_millis(33);
+
+/// Frames the embedded preview still captures after the scene changed, so
+/// the render catches up (~0.5 s); a static scene sends nothing.
+const SETTLE_FRAMES: u32 = 15;
+
+/// The preview rendered offscreen for display inside the editor. Dropping
+/// the handle stops the renderer.
+pub struct EmbeddedPreview {
+    stop: Arc<AtomicBool>,
+    state: ThreadState,
+    _thread: thread::JoinHandle<()>,
+}
+
+impl EmbeddedPreview {
+    /// Render `scene_rx`'s scenes at `size` on a background thread, the way
+    /// the preview window would. Frames arrive on the returned channel for a
+    /// short while after each scene update, and are dropped while the previous
+    /// one hasn't been taken; `wake` is called after each so the UI can
+    /// repaint.
+    pub fn start(
+        scene_rx: Receiver<SceneDoc>,
+        size: (u32, u32),
+        wake: impl Fn() + Send + Sync + 'static,
+    ) -> (Self, Receiver<PreviewFrame>) {
+        let (frame_tx, frame_rx) = crossbeam::channel::bounded(1);
+        let stop = Arc::new(AtomicBool::new(false));
+        let wake: Arc<dyn Fn() + Send + Sync> = Arc::new(wake);
+        let stream = FrameStream {
+            tx: frame_tx,
+            wake: wake.clone(),
+            stop: stop.clone(),
+            in_flight: Arc::default(),
+            pending: Arc::default(),
+        };
+
+        let (th, state) = spawn_bevy(wake, move || {
+            let (_, view_rx) = crossbeam::channel::unbounded(); // no top-down camera here
+            let mut app = App::new();
+            app.add_plugins(offscreen_plugins(EMBEDDED_FRAME));
+            add_offscreen_target(&mut app, size);
+            app.insert_resource(SceneRx(scene_rx))
+                .insert_resource(ViewRx(view_rx))
+                .init_resource::<Spawned>()
+                .init_resource::<TopDown>()
+                .insert_resource(stream)
+                .add_systems(Startup, setup)
+                .add_systems(
+                    Update,
+                    (
+                        apply_scene_updates,
+                        select_camera,
+                        target_offscreen,
+                        stream_frames,
+                    )
+                        .chain(),
+                );
+            app.run();
+        });
+
+        (
+            Self {
+                stop,
+                state,
+                _thread: th,
+            },
+            frame_rx,
+        )
+    }
+
+    pub fn status(&self) -> PreviewStatus {
+        self.state.status()
+    }
+}
+
+impl Drop for EmbeddedPreview {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+#[derive(Resource)]
+struct FrameStream {
+    tx: Sender<PreviewFrame>,
+    wake: Arc<dyn Fn() + Send + Sync>,
+    stop: Arc<AtomicBool>, // set by the editor's handle, or once nobody reads
+    in_flight: Arc<AtomicBool>, // a capture was requested and hasn't come back
+    pending: Arc<AtomicU32>, // captures left before the view is settled
+}
+
+/// While the scene is settling after an update (or a glTF finished loading),
+/// capture the target once the previous capture arrived and hand it to the
+/// editor; exit once the editor is done with us.
+fn stream_frames(
+    mut commands: Commands,
+    target: Res<OffscreenTarget>,
+    stream: Res<FrameStream>,
+    spawned: Res<Spawned>,
+    mut loaded: EventReader<AssetEvent<Scene>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    use bevy::render::view::screenshot::{Screenshot, ScreenshotCaptured};
+    use crossbeam::channel::TrySendError;
+
+    if stream.stop.load(Ordering::Relaxed) {
+        exit.write(AppExit::Success);
+        return;
+    }
+    // apply_scene_updates only touches `Spawned` when a new doc arrived
+    let gltf_loaded = loaded.read().count() > 0;
+    if spawned.is_changed() || gltf_loaded {
+        stream.pending.store(SETTLE_FRAMES, Ordering::Relaxed);
+    }
+    if stream.pending.load(Ordering::Relaxed) == 0 || stream.in_flight.swap(true, Ordering::Relaxed)
+    {
+        return;
+    }
+    stream.pending.fetch_sub(1, Ordering::Relaxed);
+    let (tx, wake) = (stream.tx.clone(), stream.wake.clone());
+    let (stop, in_flight) = (stream.stop.clone(), stream.in_flight.clone());
+    let pending = stream.pending.clone();
+    commands.spawn(Screenshot::image(target.0.clone())).observe(
+        move |trigger: Trigger<ScreenshotCaptured>| {
+            in_flight.store(false, Ordering::Relaxed);
+            let Ok(img) = trigger.event().0.clone().try_into_dynamic() else {
+                return;
+            };
+            let img = img.to_rgba8();
+            let frame = PreviewFrame {
+                size: [img.width() as usize, img.height() as usize],
+                rgba: img.into_raw(),
+            };
+            match tx.try_send(frame) {
+                Ok(()) => wake(),
+                // editor hasn't taken the last one yet; try again next frame
+                Err(TrySendError::Full(_)) => {
+                    pending.fetch_add(1, Ordering::Relaxed);
+                }
+                Err(TrySendError::Disconnected(_)) => stop.store(true, Ordering::Relaxed),
+            }
+        },
+    );
+}
+
 fn setup(mut commands: Commands) {
-    // light + camera live outside PreviewTag so we don't wipe them
+    // fallbacks for scenes without their own camera / light; they live
+    // outside PreviewTag so we don't wipe them
     commands.spawn((
+        DefaultCamera,
         Camera3d::default(),
         Transform::from_xyz(-2.5, 4.5, 9.0).looking_at(Vec3::ZERO, Vec3::Y),
     ));
     commands.spawn((
+        TopDownCamera,
+        Camera3d::default(),
+        Camera {
+            is_active: false,
+            ..default()
+        },
+        Projection::Orthographic(OrthographicProjection {
+            scaling_mode: bevy::render::camera::ScalingMode::WindowSize,
+            ..OrthographicProjection::default_3d()
+        }),
+        Transform::from_xyz(0.0, TOP_DOWN_HEIGHT, 0.0).looking_at(Vec3::ZERO, Vec3::NEG_Z),
+    ));
+    commands.spawn((
+        DefaultLight,
         PointLight {
             shadows_enabled: true,
             ..default()
@@ -67,74 +511,343 @@ fn setup(mut commands: Commands) {
     ));
 }
 
-/// Poll the channel; if there’s a new SceneDoc, rebuild PreviewTag entities.
+/// Scene entity id -> the Bevy entity built for it, plus what it was built
+/// from so the next doc can be diffed against it.
+#[derive(Resource, Default)]
+struct Spawned(HashMap<String, SpawnedEntity>);
+
+struct SpawnedEntity {
+    entity: Entity,
+    components: Vec<ComponentDoc>, // first of each type only
+    parent: Option<String>,        // None also when the link was dropped (cycle/unknown)
+    hidden: bool,
+}
+
+/// Poll the channel; if there’s a new SceneDoc, bring the PreviewTag entities
+/// in line with it. Only entities whose data changed are touched, and mesh /
+/// material assets are only recreated when their own component changed.
+#[allow(clippy::too_many_arguments)]
 fn apply_scene_updates(
     mut commands: Commands,
     rx: Res<SceneRx>,
+    mut spawned: ResMut<Spawned>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
-    query_existing: Query<Entity, With<PreviewTag>>,
+    asset_server: Res<AssetServer>,
+    mut default_light: Query<&mut Visibility, With<DefaultLight>>,
 ) {
-    let doc = match rx.0.try_recv() {
-        Ok(d) => d,
-        Err(TryRecvError::Empty) => return,
-        Err(TryRecvError::Disconnected) => return,
+    // only the newest doc matters
+    let Some(doc) = rx.0.try_iter().last() else {
+        return;
     };
 
-    // clear old content
-    for e in &query_existing {
-        commands.entity(e).despawn_recursive();
+    // the default light gives way to the scene's, so authored lighting and
+    // shadows show as-is (cameras: see select_camera)
+    let scene_has_light = doc
+        .entities
+        .iter()
+        .any(|e| e.component("PointLight").is_some());
+    for mut vis in &mut default_light {
+        *vis = if scene_has_light {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
     }
 
-    // rebuild from SceneDoc (same logic as your game loader)
-    for ent in doc.entities {
-        let mut transform = Transform::default();
-        let mut want_mesh: Option<Mesh3d> = None;
-        let mut want_mat: Option<MeshMaterial3d<StandardMaterial>> = None;
+    let mut seen_ids = HashSet::new();
+    let mut wanted_parents = Vec::new();
+    for (i, ent) in doc.entities.iter().enumerate() {
+        if !seen_ids.insert(ent.id.as_str()) {
+            warn!("entity id '{}' used twice; later copy ignored", ent.id);
+            continue;
+        }
+
+        // first component of each type wins (see EntityDoc::component)
+        let mut types = HashSet::new();
+        let components: Vec<ComponentDoc> = ent
+            .components
+            .iter()
+            .filter(|c| {
+                let first = types.insert(c.type_id.as_str());
+                if !first {
+                    warn!("entity '{}': duplicate {} ignored", ent.id, c.type_id);
+                }
+                first
+            })
+            .cloned()
+            .collect();
 
-        for c in ent.components {
-            match c.type_id.as_str() {
+        let prev = spawned.0.remove(&ent.id);
+        let entity = match &prev {
+            Some(prev) => prev.entity,
+            None => commands.spawn(PreviewTag).id(),
+        };
+        let old = prev
+            .as_ref()
+            .map(|p| p.components.as_slice())
+            .unwrap_or_default();
+        apply_components(
+            &mut commands.entity(entity),
+            old,
+            &components,
+            &mut meshes,
+            &mut materials,
+            &asset_server,
+        );
+
+        if prev.as_ref().is_none_or(|p| p.hidden != ent.hidden) {
+            commands.entity(entity).insert(if ent.hidden {
+                Visibility::Hidden
+            } else {
+                Visibility::Inherited
+            });
+        }
+
+        // a hand-edited file can contain a parent cycle; Bevy must never see one
+        let parent = match &ent.parent {
+            Some(_) if doc.parent_of(i).is_some_and(|p| doc.is_ancestor(i, p)) => {
+                warn!("entity '{}': parent cycle ignored", ent.id);
+                None
+            }
+            Some(parent) if doc.index_of(parent).is_none() => {
+                warn!("entity '{}': unknown parent '{parent}' ignored", ent.id);
+                None
+            }
+            p => p.clone(),
+        };
+        wanted_parents.push((ent.id.clone(), entity, parent.clone()));
+
+        spawned.0.insert(
+            ent.id.clone(),
+            SpawnedEntity {
+                entity,
+                components,
+                parent: prev.and_then(|p| p.parent), // updated below
+                hidden: ent.hidden,
+            },
+        );
+    }
+
+    // (re)link parents once every entity exists; transforms become relative
+    for (id, entity, parent) in wanted_parents {
+        let entry = spawned.0.get(&id).expect("inserted above");
+        if entry.parent == parent {
+            continue;
+        }
+        match parent.as_ref().and_then(|p| spawned.0.get(p)) {
+            Some(p) => {
+                commands.entity(entity).insert(ChildOf(p.entity));
+            }
+            None => {
+                commands.entity(entity).remove::<ChildOf>();
+            }
+        }
+        spawned.0.get_mut(&id).expect("inserted above").parent = parent;
+    }
+
+    // entities gone from the doc; their children were relinked above
+    spawned.0.retain(|id, e| {
+        let keep = seen_ids.contains(id.as_str());
+        if !keep {
+            commands.entity(e.entity).try_despawn(); // may already be gone with its parent
+        }
+        keep
+    });
+}
+
+/// Height of the top-down camera above the origin plane.
+const TOP_DOWN_HEIGHT: f32 = 100.0;
+
+/// Take the newest `TopDownView` and move the top-down camera to match.
+fn apply_view_updates(
+    rx: Res<ViewRx>,
+    mut top_down: ResMut<TopDown>,
+    mut cams: Query<(&mut Transform, &mut Projection), With<TopDownCamera>>,
+) {
+    let Some(view) = rx.0.try_iter().last() else {
+        return;
+    };
+    top_down.0 = view;
+    let Some(view) = view else {
+        return;
+    };
+    for (mut transform, mut projection) in &mut cams {
+        let (x, z) = view.center;
+        // up = -Z puts +z at the bottom of the screen, like the 2D view
+        *transform = Transform::from_xyz(x, TOP_DOWN_HEIGHT, z)
+            .looking_at(Vec3::new(x, 0.0, z), Vec3::NEG_Z);
+        if let Projection::Orthographic(ortho) = projection.as_mut() {
+            ortho.scale = 1.0 / view.zoom.max(f32::EPSILON); // WindowSize: 1 unit = 1 px at scale 1
+        }
+    }
+}
+
+/// Exactly one kind of camera renders: the top-down one while it's on,
+/// else the scene's own cameras, else the default from `setup`.
+fn select_camera(
+    top_down: Res<TopDown>,
+    mut cams: Query<(&mut Camera, Has<TopDownCamera>, Has<DefaultCamera>)>,
+) {
+    let scene_has_camera = cams.iter().any(|(_, top, default)| !top && !default);
+    for (mut cam, top, default) in &mut cams {
+        let active = match (top_down.0.is_some(), top, default) {
+            (true, top, _) => top,
+            (false, true, _) => false,
+            (false, false, true) => !scene_has_camera,
+            (false, false, false) => true,
+        };
+        // only write on change so Bevy's change detection stays quiet
+        if cam.is_active != active {
+            cam.is_active = active;
+        }
+    }
+}
+
+/// Update one entity's Bevy components from its scene components, touching
+/// only the types whose data changed between `old` and `new`.
+fn apply_components(
+    ec: &mut EntityCommands,
+    old: &[ComponentDoc],
+    new: &[ComponentDoc],
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    asset_server: &AssetServer,
+) {
+    let find = |list: &[ComponentDoc], ty: &str| list.iter().find(|c| c.type_id == ty).cloned();
+    for ty in COMPONENT_TYPES {
+        let (before, after) = (find(old, ty), find(new, ty));
+        if before == after {
+            continue;
+        }
+        let Some(c) = after else {
+            match ty {
                 "Transform" => {
-                    if let Some((x, y, z)) = c.data.translation {
-                        transform.translation = Vec3::new(x, y, z);
-                    }
+                    ec.insert(Transform::default());
+                }
+                "Mesh3d" => {
+                    ec.remove::<(Mesh3d, SceneRoot)>();
+                }
+                "Material3d" => {
+                    ec.remove::<MeshMaterial3d<StandardMaterial>>();
+                }
+                "PointLight" => {
+                    ec.remove::<PointLight>();
+                }
+                "Camera3d" => {
+                    ec.remove::<(Camera3d, Camera, Projection)>();
+                }
+                _ => {}
+            }
+            continue;
+        };
+        match ty {
+            "Transform" => {
+                let mut transform = Transform::default();
+                if let Some((x, y, z)) = c.data.translation {
+                    transform.translation = Vec3::new(x, y, z);
+                }
+                if let Some((x, y, z)) = c.data.scale {
+                    transform.scale = Vec3::new(x, y, z);
+                }
+                // look_at fully determines the rotation; Euler angles only
+                // apply without it
+                if let Some((x, y, z)) = c.data.look_at {
+                    transform.look_at(Vec3::new(x, y, z), Vec3::Y);
+                } else {
                     if let Some(deg) = c.data.rot_x_deg {
                         transform.rotate_x(deg.to_radians());
                     }
-                    if let Some((x, y, z)) = c.data.look_at {
-                        transform.look_at(Vec3::new(x, y, z), Vec3::Y);
+                    if let Some(deg) = c.data.rot_y_deg {
+                        transform.rotate_y(deg.to_radians());
                     }
                 }
-                "Mesh3d" => match c.data.shape.as_deref() {
-                    Some("Circle") => {
-                        let r = c.data.radius.unwrap_or(1.0);
-                        want_mesh = Some(Mesh3d(meshes.add(Circle::new(r))));
-                    }
-                    Some("Cuboid") => {
-                        let x = c.data.x.unwrap_or(1.0);
-                        let y = c.data.y.unwrap_or(1.0);
-                        let z = c.data.z.unwrap_or(1.0);
-                        want_mesh = Some(Mesh3d(meshes.add(Cuboid::new(x, y, z))));
-                    }
-                    _ => {}
-                },
-                "Material3d" => {
-                    let (r, g, b, a) = c.data.color.unwrap_or((1.0, 1.0, 1.0, 1.0));
-                    want_mat = Some(MeshMaterial3d(
-                        materials.add(Color::linear_rgba(r, g, b, a)),
+                ec.insert(transform);
+            }
+            "Mesh3d" => match c.data.shape.as_deref() {
+                Some("Circle") => {
+                    let r = c.data.radius.unwrap_or(1.0).max(MIN_MESH_DIM);
+                    ec.remove::<SceneRoot>();
+                    ec.insert(Mesh3d(meshes.add(Circle::new(r))));
+                }
+                Some("Cuboid") => {
+                    // a zero/negative size would make a degenerate mesh
+                    let x = c.data.x.unwrap_or(1.0).max(MIN_MESH_DIM);
+                    let y = c.data.y.unwrap_or(1.0).max(MIN_MESH_DIM);
+                    let z = c.data.z.unwrap_or(1.0).max(MIN_MESH_DIM);
+                    ec.remove::<SceneRoot>();
+                    ec.insert(Mesh3d(meshes.add(Cuboid::new(x, y, z))));
+                }
+                // the file's first glTF scene, spawned as children
+                Some("Asset") if c.data.mesh_path.as_deref().is_some_and(|p| !p.is_empty()) => {
+                    let path = c.data.mesh_path.clone().unwrap_or_default();
+                    ec.remove::<Mesh3d>();
+                    ec.insert(SceneRoot(
+                        asset_server.load(GltfAssetLabel::Scene(0).from_asset(path)),
                     ));
                 }
-                _ => {}
+                _ => {
+                    ec.remove::<(Mesh3d, SceneRoot)>();
+                }
+            },
+            "Material3d" => {
+                let (r, g, b, a) = c.data.color.unwrap_or((1.0, 1.0, 1.0, 1.0));
+                let mut mat = StandardMaterial {
+                    base_color: Color::linear_rgba(r, g, b, a),
+                    alpha_mode: if a < 1.0 {
+                        AlphaMode::Blend
+                    } else {
+                        AlphaMode::Opaque
+                    },
+                    ..default()
+                };
+                if let Some(m) = c.data.metallic {
+                    mat.metallic = m;
+                }
+                if let Some(rough) = c.data.roughness {
+                    mat.perceptual_roughness = rough;
+                }
+                if let Some((er, eg, eb)) = c.data.emissive {
+                    mat.emissive = LinearRgba::rgb(er, eg, eb);
+                }
+                ec.insert(MeshMaterial3d(materials.add(mat)));
             }
-        }
-
-        let id = commands.spawn((PreviewTag, transform)).id();
-        let mut ec = commands.entity(id);
-        if let Some(m) = want_mesh {
-            ec.insert(m);
-        }
-        if let Some(mat) = want_mat {
-            ec.insert(mat);
+            "PointLight" => {
+                let mut light = PointLight {
+                    shadows_enabled: c.data.shadows_enabled.unwrap_or(false),
+                    ..default()
+                };
+                if let Some(i) = c.data.intensity {
+                    light.intensity = i;
+                }
+                if let Some((r, g, b, a)) = c.data.light_color {
+                    light.color = Color::linear_rgba(r, g, b, a);
+                }
+                ec.insert(light);
+            }
+            "Camera3d" => {
+                let projection = match c.data.projection.as_deref() {
+                    Some("Orthographic") => Projection::Orthographic(OrthographicProjection {
+                        scaling_mode: bevy::render::camera::ScalingMode::FixedVertical {
+                            viewport_height: 10.0,
+                        },
+                        ..OrthographicProjection::default_3d()
+                    }),
+                    _ => {
+                        let mut p = PerspectiveProjection::default();
+                        if let Some(deg) = c.data.fov_deg {
+                            p.fov = deg.to_radians();
+                        }
+                        Projection::Perspective(p)
+                    }
+                };
+                let mut camera = Camera::default();
+                if let Some((r, g, b, a)) = c.data.clear_color {
+                    camera.clear_color = ClearColorConfig::Custom(Color::linear_rgba(r, g, b, a));
+                }
+                ec.insert((Camera3d::default(), camera, projection));
+            }
+            _ => {}
         }
     }
 }
@@ -4,27 +4,470 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct SceneDoc {
     pub entities: Vec<EntityDoc>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+impl SceneDoc {
+    /// True if an entity other than `except` already uses `id`.
+    pub fn id_taken(&self, id: &str, except: Option<usize>) -> bool {
+        self.entities
+            .iter()
+            .enumerate()
+            .any(|(i, e)| Some(i) != except && e.id == id)
+    }
+
+    /// Attached scripts whose name isn't in `known` (the current schema), as
+    /// (entity id, script name).
+    pub fn orphaned_scripts(&self, known: &[&str]) -> Vec<(String, String)> {
+        self.entities
+            .iter()
+            .flat_map(|e| {
+                e.scripts
+                    .iter()
+                    .filter(|a| !known.contains(&a.name.as_str()))
+                    .map(|a| (e.id.clone(), a.name.clone()))
+            })
+            .collect()
+    }
+
+    /// Detach every script not in `known`; returns how many went.
+    pub fn remove_orphaned_scripts(&mut self, known: &[&str]) -> usize {
+        let mut removed = 0;
+        for e in &mut self.entities {
+            let before = e.scripts.len();
+            e.scripts.retain(|a| known.contains(&a.name.as_str()));
+            removed += before - e.scripts.len();
+        }
+        removed
+    }
+
+    /// `base` if it's free, otherwise the first free `base_2`, `base_3`, …
+    pub fn unique_id(&self, base: &str) -> String {
+        if !self.id_taken(base, None) {
+            return base.to_string();
+        }
+        (2..)
+            .map(|n| format!("{base}_{n}"))
+            .find(|id| !self.id_taken(id, None))
+            .expect("unbounded suffix search")
+    }
+
+    /// Append a copy of entity `idx` with a fresh id, nudged by `offset` in
+    /// xz so it doesn't sit exactly on top of the original. Returns its index.
+    pub fn duplicate_entity(&mut self, idx: usize, offset: f32) -> Option<usize> {
+        let mut copy = self.entities.get(idx)?.clone();
+        copy.id = self.unique_id(&format!("{}_copy", copy.id));
+        for c in &mut copy.components {
+            if c.type_id == "Transform"
+                && let Some((x, y, z)) = c.data.translation
+            {
+                c.data.translation = Some((x + offset, y, z + offset));
+            }
+        }
+        self.entities.push(copy);
+        Some(self.entities.len() - 1)
+    }
+
+    /// Rename entity `idx`. This is the one place ids change, so anything
+    /// that references entities by id should be updated here too.
+    ///
+    /// Returns `Ok(false)` when the id is unchanged.
+    pub fn rename_entity(&mut self, idx: usize, new_id: &str) -> Result<bool> {
+        let new_id = new_id.trim();
+        if new_id.is_empty() {
+            anyhow::bail!("entity id can't be empty");
+        }
+        let Some(ent) = self.entities.get(idx) else {
+            anyhow::bail!("no entity at index {idx}");
+        };
+        if ent.id == new_id {
+            return Ok(false);
+        }
+        if self.id_taken(new_id, Some(idx)) {
+            anyhow::bail!("id '{new_id}' is already used by another entity");
+        }
+        let old_id = std::mem::replace(&mut self.entities[idx].id, new_id.to_string());
+        for e in &mut self.entities {
+            if e.parent.as_deref() == Some(old_id.as_str()) {
+                e.parent = Some(new_id.to_string());
+            }
+        }
+        Ok(true)
+    }
+
+    pub fn index_of(&self, id: &str) -> Option<usize> {
+        self.entities.iter().position(|e| e.id == id)
+    }
+
+    /// Index of entity `idx`'s parent; None for roots and dangling references.
+    pub fn parent_of(&self, idx: usize) -> Option<usize> {
+        let parent = self.entities.get(idx)?.parent.as_deref()?;
+        self.index_of(parent)
+    }
+
+    /// Whether `ancestor` is `idx` itself or one of its parents.
+    pub fn is_ancestor(&self, ancestor: usize, idx: usize) -> bool {
+        let mut cur = Some(idx);
+        // bounded walk: a hand-edited file may still contain a cycle
+        for _ in 0..=self.entities.len() {
+            match cur {
+                Some(i) if i == ancestor => return true,
+                Some(i) => cur = self.parent_of(i),
+                None => return false,
+            }
+        }
+        false
+    }
+
+    /// Make `parent` (an entity id) the parent of `idx`, or detach it with
+    /// `None`. Rejects unknown ids and anything that would form a cycle.
+    pub fn set_parent(&mut self, idx: usize, parent: Option<&str>) -> Result<()> {
+        if idx >= self.entities.len() {
+            anyhow::bail!("no entity at index {idx}");
+        }
+        if let Some(parent) = parent {
+            let Some(p) = self.index_of(parent) else {
+                anyhow::bail!("no entity with id '{parent}'");
+            };
+            if self.is_ancestor(idx, p) {
+                anyhow::bail!("'{parent}' is a descendant of this entity");
+            }
+        }
+        self.entities[idx].parent = parent.map(str::to_string);
+        Ok(())
+    }
+
+    /// Remove entity `idx`. Its children move up to its own parent, so no
+    /// `parent` reference is left dangling.
+    pub fn remove_entity(&mut self, idx: usize) -> Option<EntityDoc> {
+        if idx >= self.entities.len() {
+            return None;
+        }
+        let removed = self.entities.remove(idx);
+        for e in &mut self.entities {
+            if e.parent.as_deref() == Some(removed.id.as_str()) {
+                e.parent = removed.parent.clone();
+            }
+        }
+        Some(removed)
+    }
+
+    /// Move entity `from` to just before its sibling `onto`, or just after
+    /// it when `below`, and return its new index. `tree_order` keeps siblings
+    /// in file order, so in the tree it lands next to `onto` (after its
+    /// subtree when `below`). None if the two aren't distinct siblings.
+    pub fn move_beside(&mut self, from: usize, onto: usize, below: bool) -> Option<usize> {
+        if from == onto
+            || from >= self.entities.len()
+            || onto >= self.entities.len()
+            || self.parent_of(from) != self.parent_of(onto)
+        {
+            return None;
+        }
+        let insert_at = if below { onto + 1 } else { onto };
+        // `insert_at` counts the moved entity, which leaves its old slot first
+        let to = if from < insert_at {
+            insert_at - 1
+        } else {
+            insert_at
+        };
+        let ent = self.entities.remove(from);
+        self.entities.insert(to, ent);
+        Some(to)
+    }
+
+    /// Entities in depth-first tree order as `(index, depth)`. Siblings keep
+    /// their file order; dangling parents count as roots and entities caught
+    /// in a cycle are appended at depth 0.
+    pub fn tree_order(&self) -> Vec<(usize, usize)> {
+        let parents: Vec<Option<usize>> = (0..self.entities.len())
+            .map(|i| self.parent_of(i))
+            .collect();
+        let mut out = Vec::with_capacity(self.entities.len());
+        let mut visited = vec![false; self.entities.len()];
+
+        fn visit(
+            i: usize,
+            depth: usize,
+            parents: &[Option<usize>],
+            visited: &mut [bool],
+            out: &mut Vec<(usize, usize)>,
+        ) {
+            if std::mem::replace(&mut visited[i], true) {
+                return;
+            }
+            out.push((i, depth));
+            for (c, p) in parents.iter().enumerate() {
+                if *p == Some(i) {
+                    visit(c, depth + 1, parents, visited, out);
+                }
+            }
+        }
+
+        for i in 0..self.entities.len() {
+            if parents[i].is_none() {
+                visit(i, 0, &parents, &mut visited, &mut out);
+            }
+        }
+        for i in 0..self.entities.len() {
+            visit(i, 0, &parents, &mut visited, &mut out);
+        }
+        out
+    }
+
+    /// Translation of `idx` in world space: its own, carried through each
+    /// ancestor's Transform (scale, then rotation, then translation) as
+    /// Bevy composes them for children.
+    pub fn world_translation(&self, idx: usize) -> Option<(f32, f32, f32)> {
+        let local = self.entities.get(idx)?.component("Transform");
+        let t = local.and_then(|c| c.data.translation);
+        Some(self.parent_to_world(idx, t.unwrap_or((0.0, 0.0, 0.0))))
+    }
+
+    /// Point `v`, given in the space `idx`'s translation is written in (its
+    /// parent's), in world space.
+    pub fn parent_to_world(&self, idx: usize, v: (f32, f32, f32)) -> (f32, f32, f32) {
+        self.ancestors(idx)
+            .into_iter()
+            .fold(v, |v, a| Frame::of(&self.entities[a]).apply(v))
+    }
+
+    /// Inverse of `parent_to_world`: world point `w` in the space `idx`'s
+    /// translation is written in.
+    pub fn world_to_parent(&self, idx: usize, w: (f32, f32, f32)) -> (f32, f32, f32) {
+        self.ancestors(idx)
+            .into_iter()
+            .rev()
+            .fold(w, |w, a| Frame::of(&self.entities[a]).unapply(w))
+    }
+
+    /// Yaw (radians about Y) and per-axis scale that `idx`'s ancestors add to
+    /// its own Transform, for views that only draw turns about Y. Exact while
+    /// no ancestor tilts off the ground plane.
+    pub fn parent_yaw_scale(&self, idx: usize) -> (f32, (f32, f32, f32)) {
+        self.ancestors(idx)
+            .into_iter()
+            .fold((0.0, (1.0, 1.0, 1.0)), |(yaw, s), a| {
+                let f = Frame::of(&self.entities[a]);
+                (
+                    yaw + f.yaw(),
+                    (s.0 * f.scale.0, s.1 * f.scale.1, s.2 * f.scale.2),
+                )
+            })
+    }
+
+    /// Whether `idx` is hidden, itself or through an ancestor: like Bevy's
+    /// inherited visibility, hiding a parent hides its subtree.
+    pub fn hidden_in_tree(&self, idx: usize) -> bool {
+        self.entities.get(idx).is_some_and(|e| e.hidden)
+            || self
+                .ancestors(idx)
+                .into_iter()
+                .any(|a| self.entities[a].hidden)
+    }
+
+    /// Parents of `idx`, nearest first, stopping at a cycle.
+    fn ancestors(&self, idx: usize) -> Vec<usize> {
+        let mut out = Vec::new();
+        let mut cur = self.parent_of(idx);
+        while let Some(p) = cur {
+            if p == idx || out.contains(&p) {
+                break; // cycle
+            }
+            out.push(p);
+            cur = self.parent_of(p);
+        }
+        out
+    }
+}
+
+/// An entity's Transform as applied to its children's translations:
+/// `translation + rotation · (scale ⊙ v)`.
+struct Frame {
+    translation: (f32, f32, f32),
+    axes: [(f32, f32, f32); 3], // rotation matrix columns
+    scale: (f32, f32, f32),
+}
+
+impl Frame {
+    fn of(ent: &EntityDoc) -> Self {
+        let data = ent.component("Transform").map(|c| &c.data);
+        let translation = data.and_then(|d| d.translation).unwrap_or((0.0, 0.0, 0.0));
+        let scale = data.and_then(|d| d.scale).unwrap_or((1.0, 1.0, 1.0));
+        let axes = match data {
+            // as `Transform::looking_at(target, Vec3::Y)`
+            Some(CompData {
+                look_at: Some(target),
+                ..
+            }) => {
+                let back = normalized(sub(translation, *target));
+                let right = normalized(cross((0.0, 1.0, 0.0), back));
+                if right == (0.0, 0.0, 0.0) {
+                    IDENTITY // looking along Y, or at itself
+                } else {
+                    [right, cross(back, right), back]
+                }
+            }
+            // rotate_x then rotate_y, like the preview and the export
+            Some(d) => {
+                let (sx, cx) = d.rot_x_deg.unwrap_or(0.0).to_radians().sin_cos();
+                let (sy, cy) = d.rot_y_deg.unwrap_or(0.0).to_radians().sin_cos();
+                [
+                    (cy, 0.0, -sy),
+                    (sy * sx, cx, cy * sx),
+                    (sy * cx, -sx, cy * cx),
+                ]
+            }
+            None => IDENTITY,
+        };
+        Frame {
+            translation,
+            axes,
+            scale,
+        }
+    }
+
+    /// Turn about Y of the rotation, read off where it sends +x.
+    fn yaw(&self) -> f32 {
+        let (x, _, z) = self.axes[0];
+        (-z).atan2(x)
+    }
+
+    fn apply(&self, v: (f32, f32, f32)) -> (f32, f32, f32) {
+        let [a, b, c] = self.axes;
+        let (x, y, z) = (v.0 * self.scale.0, v.1 * self.scale.1, v.2 * self.scale.2);
+        let t = self.translation;
+        (
+            t.0 + a.0 * x + b.0 * y + c.0 * z,
+            t.1 + a.1 * x + b.1 * y + c.1 * z,
+            t.2 + a.2 * x + b.2 * y + c.2 * z,
+        )
+    }
+
+    fn unapply(&self, w: (f32, f32, f32)) -> (f32, f32, f32) {
+        let d = sub(w, self.translation);
+        let [a, b, c] = self.axes;
+        // the axes are orthonormal, so the inverse rotation is the transpose
+        let unscale = |v: f32, s: f32| if s == 0.0 { 0.0 } else { v / s };
+        (
+            unscale(dot(a, d), self.scale.0),
+            unscale(dot(b, d), self.scale.1),
+            unscale(dot(c, d), self.scale.2),
+        )
+    }
+}
+
+const IDENTITY: [(f32, f32, f32); 3] = [(1.0, 0.0, 0.0), (0.0, 1.0, 0.0), (0.0, 0.0, 1.0)];
+
+fn sub(a: (f32, f32, f32), b: (f32, f32, f32)) -> (f32, f32, f32) {
+    (a.0 - b.0, a.1 - b.1, a.2 - b.2)
+}
+
+fn dot(a: (f32, f32, f32), b: (f32, f32, f32)) -> f32 {
+    a.0 * b.0 + a.1 * b.1 + a.2 * b.2
+}
+
+fn cross(a: (f32, f32, f32), b: (f32, f32, f32)) -> (f32, f32, f32) {
+    (
+        a.1 * b.2 - a.2 * b.1,
+        a.2 * b.0 - a.0 * b.2,
+        a.0 * b.1 - a.1 * b.0,
+    )
+}
+
+/// `v` scaled to unit length; zero for a (near-)zero vector.
+fn normalized(v: (f32, f32, f32)) -> (f32, f32, f32) {
+    let len = dot(v, v).sqrt();
+    if len < 1e-6 {
+        (0.0, 0.0, 0.0)
+    } else {
+        (v.0 / len, v.1 / len, v.2 / len)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct EntityDoc {
     pub id: String,
     pub components: Vec<ComponentDoc>,
     #[serde(default)]
     pub scripts: Vec<AttachedScript>,
+    /// Hidden in the editor's views and spawned with `Visibility::Hidden`.
+    /// Saved in the scene RON, so a game loading the file sees it too.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub hidden: bool,
+    /// Id of the parent entity; the Transform is then relative to it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    /// Read-only in the editor: no viewport dragging, inspector edits, bulk
+    /// operations or deleting until unlocked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locked: Option<bool>,
+}
+
+/// Component types the editor and preview understand.
+pub const COMPONENT_TYPES: [&str; 5] = [
+    "Transform",
+    "Mesh3d",
+    "Material3d",
+    "PointLight",
+    "Camera3d",
+];
+
+impl EntityDoc {
+    pub fn is_locked(&self) -> bool {
+        self.locked == Some(true)
+    }
+
+    /// An entity holds at most one component per type. If a file has more,
+    /// the first one wins everywhere (inspector, 2D view, Bevy preview) and
+    /// the rest are flagged as duplicates.
+    pub fn component(&self, type_id: &str) -> Option<&ComponentDoc> {
+        self.components.iter().find(|c| c.type_id == type_id)
+    }
+
+    /// Mutable counterpart of [`EntityDoc::component`] (same first-wins rule).
+    pub fn component_mut(&mut self, type_id: &str) -> Option<&mut ComponentDoc> {
+        self.components.iter_mut().find(|c| c.type_id == type_id)
+    }
+
+    /// Whether `components[idx]` repeats a type that already appeared earlier.
+    pub fn is_duplicate_component(&self, idx: usize) -> bool {
+        let Some(c) = self.components.get(idx) else {
+            return false;
+        };
+        self.components[..idx]
+            .iter()
+            .any(|other| other.type_id == c.type_id)
+    }
+}
+
+/// A reusable entity template, saved as `design/prefabs/<name>.ron`.
+/// Instances are plain copies; editing the prefab later doesn't touch them.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Prefab {
+    pub components: Vec<ComponentDoc>,
+    #[serde(default)]
+    pub scripts: Vec<AttachedScript>,
+}
+
+/// Whether `name` works as a prefab file stem: letters, digits, `_`, `-`.
+pub fn valid_prefab_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 pub struct AttachedScript {
     pub name: String,
     #[serde(default)]
     pub params: std::collections::BTreeMap<String, ron::Value>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct ComponentDoc {
     pub type_id: String,
     // Typed payload used by both editor and runtime
@@ -32,7 +475,7 @@ pub struct ComponentDoc {
     pub data: CompData,
 }
 
-#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq)]
 pub struct CompData {
     // Transform
     #[serde(default)]
@@ -41,10 +484,14 @@ pub struct CompData {
     pub look_at: Option<(f32, f32, f32)>,
     #[serde(default)]
     pub rot_x_deg: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rot_y_deg: Option<f32>, // yaw, applied after rot_x_deg
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale: Option<(f32, f32, f32)>,
 
     // Mesh3d
     #[serde(default)]
-    pub shape: Option<String>, // "Circle" | "Cuboid"
+    pub shape: Option<String>, // "Circle" | "Cuboid" | "Asset"
     #[serde(default)]
     pub radius: Option<f32>, // Circle
     #[serde(default)]
@@ -53,28 +500,679 @@ pub struct CompData {
     pub y: Option<f32>,
     #[serde(default)]
     pub z: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mesh_path: Option<String>, // Asset: glTF file, relative to the project root
 
-    // Material3d
+    // Material3d. Colors here and below are linear RGB with unmultiplied
+    // alpha, each 0..1: the preview passes them to `Color::linear_rgba` as is,
+    // and the editor gamma-encodes them for display.
     #[serde(default)]
     pub color: Option<(f32, f32, f32, f32)>,
+    // PBR extras; None means Bevy's StandardMaterial default and is left out of the file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metallic: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub roughness: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub emissive: Option<(f32, f32, f32)>,
 
     // PointLight
     #[serde(default)]
     pub shadows_enabled: Option<bool>,
+    #[serde(default)]
+    pub intensity: Option<f32>, // lumens; None = Bevy default
+    #[serde(default)]
+    pub light_color: Option<(f32, f32, f32, f32)>,
+
+    // Camera3d; None = Bevy default (perspective, 45° fov, global clear color)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fov_deg: Option<f32>, // vertical, perspective only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub projection: Option<String>, // "Perspective" | "Orthographic"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clear_color: Option<(f32, f32, f32, f32)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// A data problem in the scene that rustc can't see.
+#[derive(Debug, Clone)]
+pub struct ValidationIssue {
+    pub entity: String, // entity id
+    pub severity: Severity,
+    pub message: String,
+}
+
+const MESH_SHAPES: [&str; 3] = ["Circle", "Cuboid", "Asset"];
+
+/// Smallest radius / cuboid side used for meshes; anything below (zero,
+/// negative) would build a degenerate mesh.
+pub const MIN_MESH_DIM: f32 = 0.001;
+const PROJECTIONS: [&str; 2] = ["Perspective", "Orthographic"];
+
+/// Check the scene for things the editor and preview would silently ignore
+/// or get wrong: duplicate ids, bad parent links, unknown types and values,
+/// components that need a partner, and entities outside `bounds`.
+pub fn validate_scene(
+    scene: &SceneDoc,
+    root: &Path,
+    bounds: Option<&WorldBounds>,
+) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    let mut push = |ent: &EntityDoc, severity, message: String| {
+        issues.push(ValidationIssue {
+            entity: ent.id.clone(),
+            severity,
+            message,
+        })
+    };
+
+    for (i, ent) in scene.entities.iter().enumerate() {
+        if ent.id.trim().is_empty() {
+            push(ent, Severity::Error, "empty entity id".into());
+        } else if scene.id_taken(&ent.id, Some(i)) && scene.index_of(&ent.id) == Some(i) {
+            // reported once, on the first holder of the id
+            push(
+                ent,
+                Severity::Error,
+                format!("id '{}' is used by more than one entity", ent.id),
+            );
+        }
+
+        if let Some(parent) = &ent.parent {
+            match scene.index_of(parent) {
+                None => push(
+                    ent,
+                    Severity::Warning,
+                    format!("parent '{parent}' doesn't exist"),
+                ),
+                Some(p) if scene.is_ancestor(i, p) => {
+                    push(ent, Severity::Error, "parent chain forms a cycle".into())
+                }
+                Some(_) => {}
+            }
+        }
+
+        for (ci, c) in ent.components.iter().enumerate() {
+            if !COMPONENT_TYPES.contains(&c.type_id.as_str()) {
+                push(
+                    ent,
+                    Severity::Warning,
+                    format!("unknown component type '{}'", c.type_id),
+                );
+            } else if ent.is_duplicate_component(ci) {
+                push(
+                    ent,
+                    Severity::Warning,
+                    format!("duplicate {} is ignored", c.type_id),
+                );
+            }
+        }
+
+        if let Some(mesh) = ent.component("Mesh3d") {
+            match mesh.data.shape.as_deref() {
+                None => push(ent, Severity::Warning, "Mesh3d has no shape".into()),
+                Some(s) if !MESH_SHAPES.contains(&s) => push(
+                    ent,
+                    Severity::Error,
+                    format!("Mesh3d has unknown shape '{s}'"),
+                ),
+                Some("Asset") => match mesh.data.mesh_path.as_deref() {
+                    None | Some("") => {
+                        push(ent, Severity::Warning, "Mesh3d asset has no path".into())
+                    }
+                    Some(path) if !root.join(path).is_file() => push(
+                        ent,
+                        Severity::Warning,
+                        format!("mesh asset '{path}' not found"),
+                    ),
+                    Some(_) => {}
+                },
+                Some(_) => {}
+            }
+        }
+        if ent.component("Material3d").is_some() && ent.component("Mesh3d").is_none() {
+            push(
+                ent,
+                Severity::Warning,
+                "Material3d without a Mesh3d has no effect".into(),
+            );
+        }
+        if let Some(cam) = ent.component("Camera3d")
+            && let Some(p) = cam.data.projection.as_deref()
+            && !PROJECTIONS.contains(&p)
+        {
+            push(
+                ent,
+                Severity::Warning,
+                format!("Camera3d has unknown projection '{p}'"),
+            );
+        }
+        if let Some(bounds) = bounds
+            && ent.component("Transform").is_some()
+            && let Some((x, _, z)) = scene.world_translation(i)
+            && !bounds.contains(x, z)
+        {
+            push(
+                ent,
+                Severity::Warning,
+                format!("at x {x}, z {z}, outside the world bounds"),
+            );
+        }
+    }
+    issues
+}
+
+/// Pretty RON for humans, as written by "Save Scene".
+fn scene_to_ron(scene: &SceneDoc) -> Result<String> {
+    let pretty = ron::ser::PrettyConfig::new()
+        .struct_names(true)
+        .compact_arrays(false)
+        .indentor("  ");
+    Ok(ron::ser::to_string_pretty(scene, pretty)?)
+}
+
+/// Rust source for a Bevy system that spawns `scene`, for games that want
+/// the design without bandana at runtime.
+///
+/// A codegen'd spawn function rather than a `DynamicScene` RON: primitive
+/// meshes and materials are assets created at spawn time, which a scene file
+/// can't express. Mapping:
+/// - id → `Name`, hidden → `Visibility::Hidden`, parent → `ChildOf`
+/// - Transform → `Transform` (look_at wins over Euler angles, as in the preview)
+/// - Mesh3d Circle/Cuboid → `meshes.add(..)`, Asset → the glTF's first scene,
+///   its path made relative to `assets/` (files outside it are skipped)
+/// - Material3d → `StandardMaterial`, PointLight, Camera3d → their Bevy types
+///
+/// Not carried over: scripts, unknown component types and duplicate
+/// components (listed as comments), and parent links that are dangling or
+/// cyclic. The output is one-way; it can't be read back into a `SceneDoc`.
+pub fn export_bevy_scene(scene: &SceneDoc) -> String {
+    use std::fmt::Write;
+    let f = float_literal;
+    let rgba = |(r, g, b, a): (f32, f32, f32, f32)| {
+        format!("Color::linear_rgba({}, {}, {}, {})", f(r), f(g), f(b), f(a))
+    };
+
+    let mut out = String::new();
+    out.push_str("// Generated by bandana from the scene design. Re-export instead of editing.\n");
+    out.push_str("use bevy::prelude::*;\n\n");
+    out.push_str("#[allow(unused_mut, unused_variables)]\n");
+    out.push_str("pub fn spawn_scene(\n");
+    out.push_str("    mut commands: Commands,\n");
+    out.push_str("    mut meshes: ResMut<Assets<Mesh>>,\n");
+    out.push_str("    mut materials: ResMut<Assets<StandardMaterial>>,\n");
+    out.push_str("    asset_server: Res<AssetServer>,\n");
+    out.push_str(") {\n");
+
+    for (i, ent) in scene.entities.iter().enumerate() {
+        let mut parts = vec![format!("Name::new({:?})", ent.id)];
+        for (ci, c) in ent.components.iter().enumerate() {
+            if ent.is_duplicate_component(ci) {
+                let _ = writeln!(out, "    // {}: duplicate {} skipped", ent.id, c.type_id);
+                continue;
+            }
+            let d = &c.data;
+            match c.type_id.as_str() {
+                "Transform" => {
+                    let (x, y, z) = d.translation.unwrap_or((0.0, 0.0, 0.0));
+                    let mut t = format!("Transform::from_xyz({}, {}, {})", f(x), f(y), f(z));
+                    if let Some((sx, sy, sz)) = d.scale {
+                        let _ =
+                            write!(t, ".with_scale(Vec3::new({}, {}, {}))", f(sx), f(sy), f(sz));
+                    }
+                    if let Some((lx, ly, lz)) = d.look_at {
+                        let _ = write!(
+                            t,
+                            ".looking_at(Vec3::new({}, {}, {}), Vec3::Y)",
+                            f(lx),
+                            f(ly),
+                            f(lz)
+                        );
+                    } else {
+                        // rotate_x then rotate_y, both in world space
+                        let rx = d.rot_x_deg.unwrap_or(0.0).to_radians();
+                        let ry = d.rot_y_deg.unwrap_or(0.0).to_radians();
+                        if rx != 0.0 || ry != 0.0 {
+                            let _ = write!(
+                                t,
+                                ".with_rotation(Quat::from_rotation_y({}) * Quat::from_rotation_x({}))",
+                                f(ry),
+                                f(rx)
+                            );
+                        }
+                    }
+                    parts.push(t);
+                }
+                "Mesh3d" => match d.shape.as_deref() {
+                    Some("Circle") => parts.push(format!(
+                        "Mesh3d(meshes.add(Circle::new({})))",
+                        f(d.radius.unwrap_or(1.0).max(MIN_MESH_DIM))
+                    )),
+                    Some("Cuboid") => parts.push(format!(
+                        "Mesh3d(meshes.add(Cuboid::new({}, {}, {})))",
+                        f(d.x.unwrap_or(1.0).max(MIN_MESH_DIM)),
+                        f(d.y.unwrap_or(1.0).max(MIN_MESH_DIM)),
+                        f(d.z.unwrap_or(1.0).max(MIN_MESH_DIM))
+                    )),
+                    Some("Asset") if d.mesh_path.as_deref().is_some_and(|p| !p.is_empty()) => {
+                        let path = d.mesh_path.as_deref().unwrap_or_default();
+                        match game_asset_path(path) {
+                            Some(asset) => parts.push(format!(
+                                "SceneRoot(asset_server.load(GltfAssetLabel::Scene(0).from_asset({asset:?})))"
+                            )),
+                            None => {
+                                let _ = writeln!(out, "    // {}: mesh asset {path:?} is outside assets/, skipped", ent.id);
+                            }
+                        }
+                    }
+                    _ => {
+                        let _ = writeln!(
+                            out,
+                            "    // {}: Mesh3d without a usable shape skipped",
+                            ent.id
+                        );
+                    }
+                },
+                "Material3d" => {
+                    let color = d.color.unwrap_or((1.0, 1.0, 1.0, 1.0));
+                    let mut m = format!("StandardMaterial {{ base_color: {}, ", rgba(color));
+                    if color.3 < 1.0 {
+                        m.push_str("alpha_mode: AlphaMode::Blend, ");
+                    }
+                    if let Some(v) = d.metallic {
+                        let _ = write!(m, "metallic: {}, ", f(v));
+                    }
+                    if let Some(v) = d.roughness {
+                        let _ = write!(m, "perceptual_roughness: {}, ", f(v));
+                    }
+                    if let Some((r, g, b)) = d.emissive {
+                        let _ = write!(
+                            m,
+                            "emissive: LinearRgba::rgb({}, {}, {}), ",
+                            f(r),
+                            f(g),
+                            f(b)
+                        );
+                    }
+                    m.push_str("..default() }");
+                    parts.push(format!("MeshMaterial3d(materials.add({m}))"));
+                }
+                "PointLight" => {
+                    let mut l = format!(
+                        "PointLight {{ shadows_enabled: {}, ",
+                        d.shadows_enabled.unwrap_or(false)
+                    );
+                    if let Some(v) = d.intensity {
+                        let _ = write!(l, "intensity: {}, ", f(v));
+                    }
+                    if let Some(c) = d.light_color {
+                        let _ = write!(l, "color: {}, ", rgba(c));
+                    }
+                    l.push_str("..default() }");
+                    parts.push(l);
+                }
+                "Camera3d" => {
+                    parts.push("Camera3d::default()".into());
+                    if let Some(c) = d.clear_color {
+                        parts.push(format!(
+                            "Camera {{ clear_color: ClearColorConfig::Custom({}), ..default() }}",
+                            rgba(c)
+                        ));
+                    }
+                    parts.push(match d.projection.as_deref() {
+                        Some("Orthographic") => "Projection::Orthographic(OrthographicProjection { \
+                             scaling_mode: bevy::render::camera::ScalingMode::FixedVertical { viewport_height: 10.0 }, \
+                             ..OrthographicProjection::default_3d() })"
+                            .into(),
+                        _ => match d.fov_deg {
+                            Some(deg) => format!(
+                                "Projection::Perspective(PerspectiveProjection {{ fov: {}, ..default() }})",
+                                f(deg.to_radians())
+                            ),
+                            None => "Projection::Perspective(PerspectiveProjection::default())".into(),
+                        },
+                    });
+                }
+                other => {
+                    let _ = writeln!(out, "    // {}: unknown component {other} skipped", ent.id);
+                }
+            }
+        }
+        if ent.hidden {
+            parts.push("Visibility::Hidden".into());
+        }
+        if !ent.scripts.is_empty() {
+            let _ = writeln!(
+                out,
+                "    // {}: {} script(s) not exported",
+                ent.id,
+                ent.scripts.len()
+            );
+        }
+        let _ = writeln!(
+            out,
+            "    let e{i} = commands.spawn(({},)).id();",
+            parts.join(", ")
+        );
+    }
+
+    for (i, ent) in scene.entities.iter().enumerate() {
+        let Some(p) = scene.parent_of(i) else {
+            continue;
+        };
+        if scene.is_ancestor(i, p) {
+            let _ = writeln!(out, "    // {}: parent cycle skipped", ent.id);
+            continue;
+        }
+        let _ = writeln!(out, "    commands.entity(e{i}).insert(ChildOf(e{p}));");
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// A project-relative `mesh_path` as the game's `AssetServer` wants it,
+/// relative to `assets/`; None for files elsewhere, which it can't load.
+fn game_asset_path(mesh_path: &str) -> Option<String> {
+    let rel = Path::new(mesh_path.trim_start_matches("./"))
+        .strip_prefix("assets")
+        .ok()?;
+    Some(rel.to_string_lossy().replace('\\', "/"))
+}
+
+/// `v` as Rust source: `{:?}` for finite values, which always prints a
+/// float literal, and the `f32` constants for NaN and the infinities.
+fn float_literal(v: f32) -> String {
+    if v.is_nan() {
+        "f32::NAN".into()
+    } else if v == f32::INFINITY {
+        "f32::INFINITY".into()
+    } else if v == f32::NEG_INFINITY {
+        "f32::NEG_INFINITY".into()
+    } else {
+        format!("{v:?}")
+    }
+}
+
+/// Read a scene written as JSON (same shape as the RON, via serde).
+pub fn load_scene_json(path: &Path) -> Result<SceneDoc> {
+    let txt = fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    serde_json::from_str(&txt).with_context(|| format!("parsing {}", path.display()))
+}
+
+/// Write `scene` as pretty-printed JSON.
+pub fn save_scene_json(scene: &SceneDoc, path: &Path) -> Result<()> {
+    write_atomic(path, &serde_json::to_string_pretty(scene)?)
+}
+
+/// Parse scene RON. Errors name the line and column and quote the line with
+/// a caret under the failing spot.
+pub fn parse_scene(txt: &str) -> Result<SceneDoc> {
+    ron::from_str(txt).map_err(|e| {
+        let pos = e.span.start;
+        let line = txt.lines().nth(pos.line.saturating_sub(1)).unwrap_or("");
+        let gutter = pos.line.to_string();
+        anyhow::anyhow!(
+            "line {}, column {}: {}\n{gutter} | {line}\n{} | {}^",
+            pos.line,
+            pos.col,
+            e.code,
+            " ".repeat(gutter.len()),
+            " ".repeat(pos.col.saturating_sub(1)),
+        )
+    })
+}
+
+/// Write via a temp file and rename, so a crash mid-write never leaves a
+/// truncated file behind.
+fn write_atomic(path: &Path, contents: &str) -> Result<()> {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp = path.with_file_name(tmp_name);
+    fs::write(&tmp, contents).with_context(|| format!("writing {}", tmp.display()))?;
+    fs::rename(&tmp, path).with_context(|| format!("replacing {}", path.display()))?;
+    Ok(())
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ProjectConfig {
     pub name: String,
     pub entry: String,        // e.g., "src/main.rs"
-    pub bevy_version: String, // stored as text; you’ll drive cargo add externally
+    pub bevy_version: String, // stored as text; "Project Settings" can `cargo add` it
+    /// Binary that writes the script schema ("Export meta").
+    #[serde(default = "default_export_bin")]
+    pub export_bin: String,
+    /// Features enabled when running `export_bin`.
+    #[serde(default = "default_export_features")]
+    pub export_features: Vec<String>,
+    /// Spacing of the viewport grid, in world units (one of `GRID_SPACINGS`).
+    #[serde(default = "default_grid_spacing")]
+    pub grid_spacing: f32,
+    /// Playable area; entities placed outside it get a validation warning.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub world_bounds: Option<WorldBounds>,
 }
 
-#[derive(Debug)]
+/// Grid spacings offered in Project Settings.
+pub const GRID_SPACINGS: [f32; 4] = [0.5, 1.0, 5.0, 10.0];
+
+fn default_grid_spacing() -> f32 {
+    1.0
+}
+
+/// A rectangle on the ground plane, as world x/z corners.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct WorldBounds {
+    pub min: (f32, f32),
+    pub max: (f32, f32),
+}
+
+impl Default for WorldBounds {
+    fn default() -> Self {
+        Self {
+            min: (-50.0, -50.0),
+            max: (50.0, 50.0),
+        }
+    }
+}
+
+impl WorldBounds {
+    pub fn contains(&self, x: f32, z: f32) -> bool {
+        (self.min.0..=self.max.0).contains(&x) && (self.min.1..=self.max.1).contains(&z)
+    }
+}
+
+fn default_export_bin() -> String {
+    "export_schema".into()
+}
+
+fn default_export_features() -> Vec<String> {
+    vec!["bandana_export".into()]
+}
+
+/// Whether `v` is a version `cargo add bevy@v` accepts: an optional `=`, `^`
+/// or `~`, then `MAJOR.MINOR[.PATCH]` with an optional `-pre.release` tail.
+pub fn valid_bevy_version(v: &str) -> bool {
+    let v = v.strip_prefix(['=', '^', '~']).unwrap_or(v);
+    let (core, pre) = match v.split_once('-') {
+        Some((core, pre)) => (core, Some(pre)),
+        None => (v, None),
+    };
+    let nums: Vec<&str> = core.split('.').collect();
+    let numeric = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    (2..=3).contains(&nums.len())
+        && nums.iter().all(|n| numeric(n))
+        && pre.is_none_or(|pre| {
+            pre.split('.')
+                .all(|id| !id.is_empty() && id.bytes().all(|b| b.is_ascii_alphanumeric()))
+        })
+}
+
+/// Targets discovered from the project's Cargo.toml (and workspace members).
+#[derive(Debug, Clone, Default)]
+pub struct CargoInfo {
+    pub packages: Vec<PackageInfo>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PackageInfo {
+    pub name: String,
+    pub bins: Vec<String>,
+    pub features: Vec<String>, // `[features]` keys, minus "default"
+}
+
+impl CargoInfo {
+    /// All binary names across packages.
+    pub fn bins(&self) -> Vec<&str> {
+        self.packages
+            .iter()
+            .flat_map(|p| p.bins.iter().map(String::as_str))
+            .collect()
+    }
+
+    /// Feature names as `cargo --features` wants them from the project root:
+    /// bare for a single package, `package/feature` in a workspace.
+    pub fn features(&self) -> Vec<String> {
+        let qualify = self.packages.len() > 1;
+        self.packages
+            .iter()
+            .flat_map(|p| {
+                p.features.iter().map(move |f| {
+                    if qualify {
+                        format!("{}/{f}", p.name)
+                    } else {
+                        f.clone()
+                    }
+                })
+            })
+            .collect()
+    }
+}
+
+/// Parse `root/Cargo.toml`. A virtual workspace manifest (no `[package]`)
+/// is followed into its `members`; simple `dir/*` globs are expanded.
+pub fn read_cargo_manifest(root: &Path) -> Result<CargoInfo> {
+    let manifest = read_toml(&root.join("Cargo.toml"))?;
+    let mut packages = Vec::new();
+
+    if manifest.contains_key("package") {
+        packages.push(package_info(root, &manifest)?);
+    }
+
+    let members = manifest
+        .get("workspace")
+        .and_then(|w| w.get("members"))
+        .and_then(|m| m.as_array());
+    for dir in members.into_iter().flatten().filter_map(|m| m.as_str()) {
+        for member in expand_member(root, dir) {
+            if member == root {
+                continue;
+            }
+            let m = read_toml(&member.join("Cargo.toml"))?;
+            if m.contains_key("package") {
+                packages.push(package_info(&member, &m)?);
+            }
+        }
+    }
+
+    if packages.is_empty() {
+        anyhow::bail!("no [package] in Cargo.toml or its workspace members");
+    }
+    Ok(CargoInfo { packages })
+}
+
+fn read_toml(path: &Path) -> Result<toml::Table> {
+    let text = fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    text.parse::<toml::Table>()
+        .with_context(|| format!("parsing {}", path.display()))
+}
+
+fn package_info(dir: &Path, manifest: &toml::Table) -> Result<PackageInfo> {
+    let package = &manifest["package"];
+    let name = package
+        .get("name")
+        .and_then(|n| n.as_str())
+        .with_context(|| format!("{}: package has no name", dir.display()))?
+        .to_string();
+
+    let mut bins: Vec<String> = manifest
+        .get("bin")
+        .and_then(|b| b.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|b| b.get("name")?.as_str().map(str::to_string))
+        .collect();
+
+    // cargo's auto-discovered targets: src/main.rs and src/bin/*
+    let autobins = package
+        .get("autobins")
+        .and_then(|a| a.as_bool())
+        .unwrap_or(true);
+    if autobins {
+        let mut auto = Vec::new();
+        if dir.join("src/main.rs").exists() {
+            auto.push(name.clone());
+        }
+        if let Ok(rd) = fs::read_dir(dir.join("src/bin")) {
+            for entry in rd.flatten() {
+                let path = entry.path();
+                let is_bin =
+                    path.extension().is_some_and(|e| e == "rs") || path.join("main.rs").exists();
+                if is_bin && let Some(stem) = path.file_stem() {
+                    auto.push(stem.to_string_lossy().into_owned());
+                }
+            }
+        }
+        auto.sort();
+        for b in auto {
+            if !bins.contains(&b) {
+                bins.push(b);
+            }
+        }
+    }
+
+    let features = manifest
+        .get("features")
+        .and_then(|f| f.as_table())
+        .map(|t| t.keys().filter(|k| *k != "default").cloned().collect())
+        .unwrap_or_default();
+
+    Ok(PackageInfo {
+        name,
+        bins,
+        features,
+    })
+}
+
+fn expand_member(root: &Path, pattern: &str) -> Vec<PathBuf> {
+    match pattern.strip_suffix("/*") {
+        Some(parent) => {
+            let mut dirs: Vec<PathBuf> = fs::read_dir(root.join(parent))
+                .into_iter()
+                .flatten()
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| p.join("Cargo.toml").exists())
+                .collect();
+            dirs.sort();
+            dirs
+        }
+        None => vec![root.join(pattern)],
+    }
+}
+
+#[derive(Debug, Serialize)]
 pub struct Diagnostic {
     pub file: PathBuf,
     pub line: u32,
     pub col: u32,
+    // end of the primary span (inclusive line, exclusive column, like rustc)
+    pub line_end: u32,
+    pub col_end: u32,
     pub msg: String,
 }
 
@@ -82,9 +1180,30 @@ pub struct Diagnostic {
 pub struct ProjectState {
     pub root: PathBuf,
     pub config: ProjectConfig,
+    pub cargo: Option<CargoInfo>, // filled by the editor after open
     pub last_diagnostics: Vec<Diagnostic>,
 
     pub design_scene: Option<SceneDoc>,
+    /// In-memory scene differs from what's on disk.
+    pub dirty: bool,
+    /// Bumped on every change to `design_scene`, so the editor can tell the
+    /// scene changed without comparing it.
+    pub revision: u64,
+    /// Scene snapshots taken before each edit, newest last.
+    pub undo: Vec<SceneDoc>,
+    /// Scenes undone since the last edit, newest last.
+    pub redo: Vec<SceneDoc>,
+    /// `undo.len()` when the scene matched the file on disk; None once that
+    /// state can't be reached by undo/redo any more.
+    saved_depth: Option<usize>,
+    /// Result of `validate_scene`, refreshed on load and after edits.
+    pub validation: Vec<ValidationIssue>,
+    /// Why the design file on disk couldn't be parsed; cleared once it can.
+    /// The project still opens, and a loaded scene is kept.
+    pub design_error: Option<String>,
+    /// A newer scene file that arrived while there were unsaved edits; the
+    /// editor asks whether to keep the edits or load this one.
+    pub disk_conflict: Option<SceneDoc>,
     design_path: Option<PathBuf>,
     design_mtime: Option<SystemTime>,
 }
@@ -100,29 +1219,175 @@ impl ProjectState {
             ron::from_str(&cfg_text).with_context(|| "parsing project.ron")?;
 
         let design_path = root.join("design/initial.scene.ron");
+        let mut design_error = None;
         let (design_scene, design_mtime) = if design_path.exists() {
             let txt = fs::read_to_string(&design_path)
                 .with_context(|| format!("reading {}", design_path.display()))?;
-            let scene: SceneDoc =
-                ron::from_str(&txt).with_context(|| "parsing design/initial.scene.ron")?;
             let mt = fs::metadata(&design_path)?.modified().ok();
-            (Some(scene), mt)
+            match parse_scene(&txt) {
+                Ok(scene) => (Some(scene), mt),
+                Err(e) => {
+                    design_error = Some(format!("design/initial.scene.ron {e}"));
+                    (None, mt)
+                }
+            }
         } else {
             (None, None)
         };
 
-        Ok(Self {
+        let mut state = Self {
             root,
             config,
+            cargo: None,
             last_diagnostics: Vec::new(),
             design_scene,
+            dirty: false,
+            revision: 0,
+            undo: Vec::new(),
+            redo: Vec::new(),
+            saved_depth: Some(0),
+            validation: Vec::new(),
+            design_error,
+            disk_conflict: None,
             design_path: if design_path.exists() {
                 Some(design_path)
             } else {
                 None
             },
             design_mtime,
-        })
+        };
+        state.revalidate();
+        Ok(state)
+    }
+
+    /// Re-read project.ron after it changed on disk. On a parse error the
+    /// current config is kept.
+    pub fn reload_config(&mut self) -> Result<()> {
+        let path = self.root.join("project.ron");
+        let text =
+            fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))?;
+        self.config = ron::from_str(&text).with_context(|| "parsing project.ron")?;
+        self.revalidate(); // the world bounds may have changed
+        Ok(())
+    }
+
+    /// Write `config` to project.ron and adopt it.
+    pub fn save_config(&mut self, config: ProjectConfig) -> Result<()> {
+        let pretty = ron::ser::PrettyConfig::new()
+            .struct_names(true)
+            .indentor("  ");
+        let text = ron::ser::to_string_pretty(&config, pretty)?;
+        write_atomic(&self.root.join("project.ron"), &text)?;
+        self.config = config;
+        self.revalidate();
+        Ok(())
+    }
+
+    /// Call after changing `design_scene` in memory.
+    pub fn mark_edited(&mut self) {
+        // an edit without its own undo step leaves the saved state behind
+        if self.saved_depth == Some(self.undo.len()) {
+            self.saved_depth = None;
+        }
+        self.dirty = true;
+        self.scene_changed();
+    }
+
+    /// Call after `design_scene` was replaced or changed in place, edit or not.
+    pub fn scene_changed(&mut self) {
+        self.revision += 1;
+        self.revalidate();
+    }
+
+    pub fn revalidate(&mut self) {
+        self.validation = self
+            .design_scene
+            .as_ref()
+            .map(|s| validate_scene(s, &self.root, self.config.world_bounds.as_ref()))
+            .unwrap_or_default();
+    }
+
+    /// Record `before` (the scene as it was prior to an edit) as one undo step.
+    pub fn push_undo(&mut self, before: SceneDoc) {
+        const MAX_UNDO: usize = 100;
+        // a saved state among the redo steps is gone with them
+        if self.saved_depth.is_some_and(|d| d > self.undo.len()) {
+            self.saved_depth = None;
+        }
+        self.undo.push(before);
+        if self.undo.len() > MAX_UNDO {
+            self.undo.remove(0);
+            self.saved_depth = self.saved_depth.and_then(|d| d.checked_sub(1));
+        }
+        self.redo.clear();
+    }
+
+    /// Step back one edit. Returns false when there's nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        let Some(prev) = self.undo.pop() else {
+            return false;
+        };
+        if let Some(cur) = self.design_scene.replace(prev) {
+            self.redo.push(cur);
+        }
+        self.history_moved();
+        true
+    }
+
+    /// Re-apply the last undone edit. Returns false when there's nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        let Some(next) = self.redo.pop() else {
+            return false;
+        };
+        if let Some(cur) = self.design_scene.replace(next) {
+            self.undo.push(cur);
+        }
+        self.history_moved();
+        true
+    }
+
+    /// After undo/redo: dirty unless that landed back on the saved state.
+    fn history_moved(&mut self) {
+        self.dirty = self.saved_depth != Some(self.undo.len());
+        self.scene_changed();
+    }
+
+    /// Whether the root has a Cargo.toml; without one there's nothing to
+    /// check, build or run.
+    pub fn has_manifest(&self) -> bool {
+        self.root.join("Cargo.toml").is_file()
+    }
+
+    /// Where "Save Scene" writes; None until a scene file exists or is chosen.
+    pub fn design_path(&self) -> Option<&Path> {
+        self.design_path.as_deref()
+    }
+
+    /// Write the scene to `path` (creating its directory) and make that the
+    /// design file from now on. Without a scene in memory an empty one is
+    /// created, so this also starts a new scene in an empty project.
+    pub fn save_design_as(&mut self, path: PathBuf) -> anyhow::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+        }
+        let previous = self.design_path.replace(path);
+        let had_scene = self.design_scene.is_some();
+        if !had_scene {
+            self.design_scene = Some(SceneDoc {
+                entities: Vec::new(),
+            });
+        }
+        let res = self.save_design();
+        if res.is_err() {
+            self.design_path = previous;
+            if !had_scene {
+                self.design_scene = None;
+            }
+        }
+        if !had_scene && res.is_ok() {
+            self.scene_changed();
+        }
+        res
     }
 
     pub fn save_design(&mut self) -> anyhow::Result<()> {
@@ -133,36 +1398,473 @@ impl ProjectState {
             anyhow::bail!("no scene in memory");
         };
 
-        // pretty RON for humans
+        write_atomic(path, &scene_to_ron(scene)?)?;
+        self.dirty = false;
+        self.saved_depth = Some(self.undo.len());
+        self.design_error = None;
+        // bump mtime so our watcher doesn’t thrash
+        self.design_mtime = fs::metadata(path).ok().and_then(|m| m.modified().ok());
+        // the real file is newer now; a leftover autosave would only confuse recovery
+        if let Some(autosave) = self.autosave_path() {
+            let _ = fs::remove_file(autosave);
+        }
+        Ok(())
+    }
+
+    /// Replace the in-memory scene with an imported one, as an undoable edit.
+    /// The design path is untouched, so saving still writes RON there.
+    pub fn import_scene(&mut self, scene: SceneDoc) {
+        if let Some(before) = self.design_scene.replace(scene) {
+            self.push_undo(before);
+        }
+        self.mark_edited();
+    }
+
+    /// Thumbnail image next to the design file, e.g.
+    /// `design/initial.scene.ron` → `design/initial.thumb.png`.
+    pub fn thumbnail_path(&self) -> Option<PathBuf> {
+        let path = self.design_path.as_ref()?;
+        let name = path.file_name()?.to_string_lossy();
+        let stem = name.split('.').next().unwrap_or("scene");
+        Some(path.with_file_name(format!("{stem}.thumb.png")))
+    }
+
+    /// The scene with asset mesh paths made absolute, for the Bevy preview
+    /// and thumbnail renderer, which don't know the project root.
+    pub fn scene_for_preview(&self) -> Option<SceneDoc> {
+        let mut scene = self.design_scene.clone()?;
+        for c in scene.entities.iter_mut().flat_map(|e| &mut e.components) {
+            if let Some(path) = &mut c.data.mesh_path {
+                *path = self.root.join(&*path).to_string_lossy().into_owned();
+            }
+        }
+        Some(scene)
+    }
+
+    /// Write `export_bevy_scene` next to the design file, e.g.
+    /// `design/initial.scene.ron` → `design/initial_scene.rs`.
+    pub fn export_bevy_scene(&self) -> anyhow::Result<PathBuf> {
+        let Some(path) = &self.design_path else {
+            anyhow::bail!("save the scene first");
+        };
+        let Some(scene) = &self.design_scene else {
+            anyhow::bail!("no scene in memory");
+        };
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let stem = name.split('.').next().unwrap_or("scene");
+        let out = path.with_file_name(format!("{stem}_scene.rs"));
+        write_atomic(&out, &export_bevy_scene(scene))?;
+        Ok(out)
+    }
+
+    pub fn prefabs_dir(&self) -> PathBuf {
+        self.root.join("design/prefabs")
+    }
+
+    /// Names of the prefabs in `prefabs_dir`, sorted.
+    pub fn list_prefabs(&self) -> Vec<String> {
+        let Ok(entries) = fs::read_dir(self.prefabs_dir()) else {
+            return Vec::new();
+        };
+        let mut names: Vec<String> = entries
+            .filter_map(|e| {
+                let path = e.ok()?.path();
+                (path.extension()? == "ron").then_some(())?;
+                Some(path.file_stem()?.to_string_lossy().into_owned())
+            })
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Save entity `idx`'s components and scripts as prefab `name`,
+    /// replacing one of the same name.
+    pub fn save_prefab(&self, name: &str, idx: usize) -> Result<PathBuf> {
+        if !valid_prefab_name(name) {
+            anyhow::bail!("prefab names may only use letters, digits, '_' and '-'");
+        }
+        let Some(ent) = self.design_scene.as_ref().and_then(|s| s.entities.get(idx)) else {
+            anyhow::bail!("no such entity");
+        };
+        let prefab = Prefab {
+            components: ent.components.clone(),
+            scripts: ent.scripts.clone(),
+        };
+        let dir = self.prefabs_dir();
+        fs::create_dir_all(&dir).with_context(|| format!("creating {}", dir.display()))?;
         let pretty = ron::ser::PrettyConfig::new()
             .struct_names(true)
             .compact_arrays(false)
             .indentor("  ");
+        let path = dir.join(format!("{name}.ron"));
+        write_atomic(&path, &ron::ser::to_string_pretty(&prefab, pretty)?)?;
+        Ok(path)
+    }
 
-        let text = ron::ser::to_string_pretty(scene, pretty)?;
-        fs::write(path, text)?;
-        // bump mtime so our watcher doesn’t thrash
-        self.design_mtime = fs::metadata(path).ok().and_then(|m| m.modified().ok());
+    /// Append a new entity built from prefab `name`, with a fresh id based
+    /// on the name, as an undoable edit. Returns its index.
+    pub fn instantiate_prefab(&mut self, name: &str) -> Result<usize> {
+        let path = self.prefabs_dir().join(format!("{name}.ron"));
+        let txt =
+            fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))?;
+        let prefab: Prefab =
+            ron::from_str(&txt).with_context(|| format!("parsing {}", path.display()))?;
+        let Some(scene) = &mut self.design_scene else {
+            anyhow::bail!("no scene in memory");
+        };
+        let before = scene.clone();
+        scene.entities.push(EntityDoc {
+            id: scene.unique_id(name),
+            components: prefab.components,
+            scripts: prefab.scripts,
+            hidden: false,
+            parent: None,
+            locked: None,
+        });
+        let idx = scene.entities.len() - 1;
+        self.push_undo(before);
+        self.mark_edited();
+        Ok(idx)
+    }
+
+    /// Recovery file next to the design file, e.g. `initial.scene.ron.autosave`.
+    pub fn autosave_path(&self) -> Option<PathBuf> {
+        let path = self.design_path.as_ref()?;
+        let mut name = path.file_name()?.to_os_string();
+        name.push(".autosave");
+        Some(path.with_file_name(name))
+    }
+
+    /// Write the in-memory scene to the recovery file. The design file itself
+    /// is never touched.
+    pub fn write_autosave(&self) -> anyhow::Result<()> {
+        let Some(path) = self.autosave_path() else {
+            anyhow::bail!("no design file");
+        };
+        let Some(scene) = &self.design_scene else {
+            anyhow::bail!("no scene in memory");
+        };
+        write_atomic(&path, &scene_to_ron(scene)?)
+    }
+
+    /// The recovery file, if there is one newer than the design file.
+    pub fn pending_autosave(&self) -> Option<PathBuf> {
+        let path = self.autosave_path()?;
+        let autosaved = fs::metadata(&path).ok()?.modified().ok()?;
+        self.design_mtime
+            .is_none_or(|saved| autosaved > saved)
+            .then_some(path)
+    }
+
+    /// Replace the in-memory scene with the recovery file's content. The
+    /// scene stays dirty until it's saved for real.
+    pub fn recover_autosave(&mut self) -> anyhow::Result<()> {
+        let Some(path) = self.autosave_path() else {
+            anyhow::bail!("no design file");
+        };
+        let txt =
+            fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))?;
+        let scene = parse_scene(&txt).with_context(|| format!("parsing {}", path.display()))?;
+        if let Some(before) = self.design_scene.replace(scene) {
+            self.push_undo(before);
+        }
+        self.mark_edited();
         Ok(())
     }
 
-    pub fn reload_design_if_changed(&mut self) {
+    pub fn discard_autosave(&self) {
+        if let Some(path) = self.autosave_path() {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    /// Re-read the scene file if it changed on disk. Returns whether a new
+    /// scene replaced the one in memory; with unsaved edits it's parked in
+    /// `disk_conflict` instead.
+    pub fn reload_design_if_changed(&mut self) -> bool {
         let Some(p) = &self.design_path else {
-            return;
+            return false;
         };
         let Ok(md) = fs::metadata(p) else {
-            return;
+            return false;
         };
         let Ok(mt) = md.modified() else {
-            return;
+            return false;
         };
-        if self.design_mtime.map(|t| mt > t).unwrap_or(true) {
-            if let Ok(txt) = fs::read_to_string(p) {
-                if let Ok(scene) = ron::from_str::<SceneDoc>(&txt) {
-                    self.design_scene = Some(scene);
-                    self.design_mtime = Some(mt);
+        if self.design_mtime.map(|t| mt > t).unwrap_or(true)
+            && let Ok(txt) = fs::read_to_string(p)
+        {
+            // either way this version has been seen; don't re-parse every frame
+            self.design_mtime = Some(mt);
+            match parse_scene(&txt) {
+                Ok(scene) if self.dirty => {
+                    self.design_error = None;
+                    self.disk_conflict = Some(scene);
+                }
+                Ok(scene) => {
+                    self.adopt_disk_scene(scene);
+                    return true;
+                }
+                // keep what's in memory (a typo mid-edit shouldn't wipe the scene)
+                Err(e) => {
+                    let name = p.file_name().unwrap_or_default().to_string_lossy();
+                    self.design_error = Some(format!("{name} {e}"));
                 }
             }
         }
+        false
+    }
+
+    /// Replace the in-memory scene with `scene` as read from disk. The old
+    /// one becomes an undo step, so the reload itself can be undone.
+    pub fn adopt_disk_scene(&mut self, scene: SceneDoc) {
+        if let Some(old) = self.design_scene.replace(scene) {
+            self.push_undo(old);
+        }
+        self.design_error = None;
+        self.disk_conflict = None;
+        self.dirty = false;
+        self.saved_depth = Some(self.undo.len());
+        self.scene_changed();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scene(ron: &str) -> SceneDoc {
+        parse_scene(ron).expect("test scene parses")
+    }
+
+    #[test]
+    fn export_writes_non_finite_floats_as_constants() {
+        let s = scene(
+            r#"(entities: [
+                (id: "lamp", components: [
+                    (type_id: "PointLight", data: (intensity: Some(inf))),
+                    (type_id: "Transform", data: (translation: Some((NaN, -inf, 1.0)))),
+                ]),
+            ])"#,
+        );
+        let src = export_bevy_scene(&s);
+        assert!(src.contains("intensity: f32::INFINITY,"), "{src}");
+        assert!(
+            src.contains("Transform::from_xyz(f32::NAN, f32::NEG_INFINITY, 1.0)"),
+            "{src}"
+        );
+    }
+
+    #[test]
+    fn export_loads_meshes_relative_to_assets() {
+        let s = scene(
+            r#"(entities: [
+                (id: "tree", components: [
+                    (type_id: "Mesh3d", data: (shape: Some("Asset"), mesh_path: Some("assets/models/tree.glb"))),
+                ]),
+                (id: "rock", components: [
+                    (type_id: "Mesh3d", data: (shape: Some("Asset"), mesh_path: Some("design/rock.glb"))),
+                ]),
+            ])"#,
+        );
+        let src = export_bevy_scene(&s);
+        assert!(src.contains(r#"from_asset("models/tree.glb")"#), "{src}");
+        assert!(
+            src.contains(r#"// rock: mesh asset "design/rock.glb" is outside assets/, skipped"#),
+            "{src}"
+        );
+        assert_eq!(src.matches("from_asset(").count(), 1, "{src}");
+    }
+
+    #[test]
+    fn move_beside_follows_the_tree() {
+        let mut s = scene(
+            r#"(entities: [
+                (id: "a", components: []),
+                (id: "a1", parent: Some("a"), components: []),
+                (id: "b", components: []),
+                (id: "b1", parent: Some("b"), components: []),
+                (id: "c", components: []),
+            ])"#,
+        );
+        let shown = |s: &SceneDoc| -> Vec<String> {
+            s.tree_order()
+                .into_iter()
+                .map(|(i, _)| s.entities[i].id.clone())
+                .collect()
+        };
+
+        // below a parent: after its whole subtree
+        let c = s.index_of("c").unwrap();
+        let to = s.move_beside(c, s.index_of("a").unwrap(), true).unwrap();
+        assert_eq!(s.entities[to].id, "c");
+        assert_eq!(shown(&s), ["a", "a1", "c", "b", "b1"]);
+
+        // above a root
+        let b = s.index_of("b").unwrap();
+        s.move_beside(b, s.index_of("a").unwrap(), false).unwrap();
+        assert_eq!(shown(&s), ["b", "b1", "a", "a1", "c"]);
+
+        // children only move among their siblings
+        let before = s.clone();
+        assert_eq!(
+            s.move_beside(s.index_of("a1").unwrap(), s.index_of("b1").unwrap(), false),
+            None
+        );
+        assert_eq!(
+            s.move_beside(s.index_of("a1").unwrap(), s.index_of("c").unwrap(), true),
+            None
+        );
+        assert_eq!(s, before);
+    }
+
+    #[test]
+    fn world_translation_composes_parent_transforms() {
+        let s = scene(
+            r#"(entities: [
+                (id: "base", components: [
+                    (type_id: "Transform", data: (translation: Some((10.0, 0.0, 0.0)),
+                        rot_y_deg: Some(90.0), scale: Some((2.0, 2.0, 2.0)))),
+                ]),
+                (id: "arm", parent: Some("base"), components: [
+                    (type_id: "Transform", data: (translation: Some((1.0, 0.0, 0.0)))),
+                ]),
+                (id: "hand", parent: Some("arm"), components: [
+                    (type_id: "Transform", data: (translation: Some((0.0, 0.0, -1.0)))),
+                ]),
+            ])"#,
+        );
+        let close = |a: (f32, f32, f32), b: (f32, f32, f32)| {
+            (a.0 - b.0).abs() < 1e-4 && (a.1 - b.1).abs() < 1e-4 && (a.2 - b.2).abs() < 1e-4
+        };
+        // +x turned 90° about Y points at -z, doubled by the parent's scale
+        let arm = s.world_translation(1).unwrap();
+        assert!(close(arm, (10.0, 0.0, -2.0)), "{arm:?}");
+        let hand = s.world_translation(2).unwrap();
+        assert!(close(hand, (8.0, 0.0, -2.0)), "{hand:?}");
+        let back = s.world_to_parent(2, hand);
+        assert!(close(back, (0.0, 0.0, -1.0)), "{back:?}");
+    }
+
+    #[test]
+    fn parent_yaw_and_scale_compose_down_the_chain() {
+        let s = scene(
+            r#"(entities: [
+                (id: "base", components: [
+                    (type_id: "Transform", data: (rot_y_deg: Some(90.0), scale: Some((2.0, 1.0, 3.0)))),
+                ]),
+                (id: "arm", parent: Some("base"), components: [
+                    (type_id: "Transform", data: (look_at: Some((1.0, 0.0, 1.0)), scale: Some((0.5, 1.0, 1.0)))),
+                ]),
+                (id: "hand", parent: Some("arm"), components: []),
+            ])"#,
+        );
+        let (yaw, scale) = s.parent_yaw_scale(0);
+        assert_eq!((yaw, scale), (0.0, (1.0, 1.0, 1.0)));
+        // turning -z to face +x+z takes -135°, on top of the base's 90°
+        let (yaw, scale) = s.parent_yaw_scale(2);
+        assert!(
+            (yaw.to_degrees() + 45.0).abs() < 1e-3,
+            "{}",
+            yaw.to_degrees()
+        );
+        assert_eq!(scale, (1.0, 1.0, 3.0));
+    }
+
+    #[test]
+    fn hiding_a_parent_hides_its_subtree() {
+        let s = scene(
+            r#"(entities: [
+                (id: "group", hidden: true, components: []),
+                (id: "child", parent: Some("group"), components: []),
+                (id: "grandchild", parent: Some("child"), components: []),
+                (id: "other", components: []),
+            ])"#,
+        );
+        assert!(s.hidden_in_tree(0) && s.hidden_in_tree(1) && s.hidden_in_tree(2));
+        assert!(!s.hidden_in_tree(3));
+    }
+
+    #[test]
+    fn undo_back_to_the_save_is_clean() {
+        let dir = std::env::temp_dir().join(format!("bandana-undo-{}", std::process::id()));
+        fs::create_dir_all(dir.join("design")).unwrap();
+        fs::write(
+            dir.join("project.ron"),
+            r#"(name: "t", entry: "src/main.rs", bevy_version: "0.16")"#,
+        )
+        .unwrap();
+        fs::write(dir.join("design/initial.scene.ron"), "(entities: [])").unwrap();
+        let mut p = ProjectState::open(&dir).unwrap();
+        let edit = |p: &mut ProjectState, id: &str| {
+            let before = p.design_scene.clone().unwrap();
+            p.design_scene.as_mut().unwrap().entities.push(EntityDoc {
+                id: id.into(),
+                components: Vec::new(),
+                scripts: Vec::new(),
+                hidden: false,
+                parent: None,
+                locked: None,
+            });
+            p.push_undo(before);
+            p.mark_edited();
+        };
+
+        edit(&mut p, "a");
+        p.save_design().unwrap();
+        edit(&mut p, "b");
+        assert!(p.dirty);
+        p.undo();
+        assert!(!p.dirty, "back at the save");
+        p.undo();
+        assert!(p.dirty, "before the save");
+        p.redo();
+        assert!(!p.dirty, "redone up to the save");
+
+        // undo past the save, then a new edit: the saved state is gone
+        p.undo();
+        edit(&mut p, "c");
+        p.undo();
+        p.redo();
+        assert!(p.dirty, "same depth as the save, different scene");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn duplicate_gets_a_fresh_id_and_offset() {
+        let mut s = scene(
+            r#"(entities: [
+                (id: "crate", components: [
+                    (type_id: "Transform", data: (translation: Some((1.0, 2.0, 3.0)))),
+                ]),
+            ])"#,
+        );
+        let original = s.entities[0].clone();
+
+        let idx = s.duplicate_entity(0, 0.5).unwrap();
+        assert_eq!(idx, 1);
+        let copy = &s.entities[idx];
+        assert_eq!(copy.id, "crate_copy");
+        let t = copy.component("Transform").unwrap().data.translation;
+        assert_eq!(t, Some((1.5, 2.0, 3.5)));
+        assert_eq!(s.entities[0], original);
+    }
+
+    #[test]
+    fn duplicate_ids_skip_taken_suffixes() {
+        let mut s = scene(
+            r#"(entities: [
+                (id: "crate", components: []),
+                (id: "crate_copy", components: []),
+                (id: "crate_copy_2", components: []),
+            ])"#,
+        );
+        let idx = s.duplicate_entity(0, 0.5).unwrap();
+        assert_eq!(s.entities[idx].id, "crate_copy_3");
+        let idx = s.duplicate_entity(0, 0.5).unwrap();
+        assert_eq!(s.entities[idx].id, "crate_copy_4");
+        assert_eq!(s.unique_id("fresh"), "fresh");
+        assert_eq!(s.duplicate_entity(99, 0.5), None);
     }
 }
@@ -1,24 +1,61 @@
 use crossbeam::channel::{Receiver, unbounded};
-use std::io::{BufRead, BufReader};
-use std::path::PathBuf;
-use std::process::{Command, Stdio};
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::Duration;
 
 pub enum RunnerMsg {
+    /// One output line, prefixed with `[out]` or `[err]` by stream.
     Line(String),
     Exited(i32),
 }
 
-pub fn start(root: PathBuf, env_overrides: &[(&str, &str)]) -> Receiver<RunnerMsg> {
-    let (tx, rx) = unbounded();
+/// What `cargo run` should build.
+pub struct RunArgs<'a> {
+    pub release: bool,
+    pub bin: Option<&'a str>, // None = cargo's default bin
+    pub features: &'a [String],
+    pub game_args: &'a [String], // passed after `--`
+}
+
+/// Handle to a running game; shared with the exit waiter.
+pub struct RunHandle(Arc<Mutex<Child>>);
+
+impl RunHandle {
+    /// Kill the process and reap it. The waiter still reports `Exited`.
+    pub fn kill(&self) {
+        let mut child = self.0.lock().unwrap();
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
 
+/// Spawn `cargo run` in `root`. Output and the exit code arrive on the
+/// returned channel; `wake` is called after every message so the UI can
+/// repaint.
+pub fn start(
+    root: &Path,
+    args: &RunArgs,
+    env_overrides: &[(&str, &str)],
+    wake: impl Fn() + Send + Sync + 'static,
+) -> io::Result<(RunHandle, Receiver<RunnerMsg>)> {
     let mut cmd = Command::new("cargo");
-    cmd.arg("run")
-        .arg("--bin")
-        .arg("sample_game")
-        .arg("--features")
-        .arg("editor-bridge")
-        .current_dir(&root)
+    cmd.arg("run");
+    if args.release {
+        cmd.arg("--release");
+    }
+    if let Some(bin) = args.bin {
+        cmd.arg("--bin").arg(bin);
+    }
+    if !args.features.is_empty() {
+        cmd.arg("--features").arg(args.features.join(","));
+    }
+    if !args.game_args.is_empty() {
+        cmd.arg("--").args(args.game_args);
+    }
+    cmd.current_dir(root)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped());
 
@@ -26,26 +63,65 @@ pub fn start(root: PathBuf, env_overrides: &[(&str, &str)]) -> Receiver<RunnerMs
         cmd.env(k, v);
     }
 
-    let mut child = cmd.spawn().expect("spawn cargo run");
+    spawn_streaming(cmd, wake)
+}
+
+/// Spawn `cargo add <spec>` in `root`, streamed like `start`.
+pub fn cargo_add(
+    root: &Path,
+    spec: &str,
+    wake: impl Fn() + Send + Sync + 'static,
+) -> io::Result<(RunHandle, Receiver<RunnerMsg>)> {
+    let mut cmd = Command::new("cargo");
+    cmd.arg("add")
+        .arg(spec)
+        .current_dir(root)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    spawn_streaming(cmd, wake)
+}
+
+fn spawn_streaming(
+    mut cmd: Command,
+    wake: impl Fn() + Send + Sync + 'static,
+) -> io::Result<(RunHandle, Receiver<RunnerMsg>)> {
+    let (tx, rx) = unbounded();
+    let wake = Arc::new(wake);
+
+    let mut child = cmd.spawn()?;
     let out = child.stdout.take().unwrap();
     let err = child.stderr.take().unwrap();
 
-    let tx2 = tx.clone();
+    let (tx2, wake2) = (tx.clone(), wake.clone());
     thread::spawn(move || {
-        for l in BufReader::new(out).lines().flatten() {
-            let _ = tx2.send(RunnerMsg::Line(l));
+        for l in BufReader::new(out).lines().map_while(Result::ok) {
+            let _ = tx2.send(RunnerMsg::Line(format!("[out] {l}")));
+            wake2();
         }
     });
-    let tx3 = tx.clone();
+    let (tx3, wake3) = (tx.clone(), wake.clone());
     thread::spawn(move || {
-        for l in BufReader::new(err).lines().flatten() {
-            let _ = tx3.send(RunnerMsg::Line(l));
+        for l in BufReader::new(err).lines().map_while(Result::ok) {
+            let _ = tx3.send(RunnerMsg::Line(format!("[err] {l}")));
+            wake3();
         }
     });
+
+    // Poll instead of wait() so the lock stays free for RunHandle::kill.
+    let child = Arc::new(Mutex::new(child));
+    let waited = child.clone();
     thread::spawn(move || {
-        let code = child.wait().ok().and_then(|s| s.code()).unwrap_or(-1);
+        let code = loop {
+            match waited.lock().unwrap().try_wait() {
+                Ok(Some(status)) => break status.code().unwrap_or(-1),
+                Ok(None) => {}
+                Err(_) => break -1,
+            }
+            thread::sleep(Duration::from_millis(100));
+        };
         let _ = tx.send(RunnerMsg::Exited(code));
+        wake();
     });
 
-    rx
+    Ok((RunHandle(child), rx))
 }